@@ -1,39 +1,54 @@
-use algae_lib::NgramData;
-use serde::{Deserialize, Serialize};
+use algae_lib::{
+    FileInfo, FormatError, InvalidBytes, NgramData, Normalization, SourceInfo, UnicodeForm,
+};
 use std::fs::File;
+use std::io::{BufReader, BufWriter};
 
-pub fn get_corpus<const N: usize>(source: &str, data: &str) -> NgramData<N>
-where
-    [char; N]: Serialize,
-    for<'a> [char; N]: Deserialize<'a>,
-{
+pub fn get_corpus<const N: usize>(source: &str, data: &str) -> NgramData<N> {
     let corpus = load_corpus(source, data);
     corpus.expand(expansion)
 }
 
-fn load_corpus<const N: usize>(source: &str, data: &str) -> NgramData<N>
-where
-    [char; N]: Serialize,
-    for<'a> [char; N]: Deserialize<'a>,
-{
+fn load_corpus<const N: usize>(source: &str, data: &str) -> NgramData<N> {
+    let normalization = Normalization {
+        line_endings: true,
+        form: Some(UnicodeForm::Nfc),
+        ascii_punctuation: true,
+        ..Normalization::default()
+    };
+    // Source is read again only if its size or modification time changed.
+    let saved = File::open(data)
+        .ok()
+        .and_then(|file| FileInfo::read(BufReader::new(file)).ok());
+    let info = FileInfo {
+        normalization,
+        source: SourceInfo::of_file(source, saved.as_ref().map(|saved| &saved.source))
+            .expect("failed to read corpus"),
+        ..FileInfo::default()
+    };
     if let Ok(file) = File::open(data) {
-        if let Ok(res) = bincode::deserialize_from(file) {
-            return res;
+        match NgramData::<N>::load(BufReader::new(file), &info) {
+            Ok(res) => return res,
+            Err(FormatError::Io(error)) => panic!("failed to read ngrams from {data}: {error}"),
+            Err(error) => println!("can't use ngrams in {data} ({error}), recalculating"),
         }
-        panic!("failed to read ngrams from: {data}");
+    } else {
+        println!("failed to open file {data}, it will be created with calculated stats");
     }
-    println!("failed to open file {data}, it will be created with calculated stats");
-    let corpus = std::fs::read_to_string(source).expect("failed to read corpus");
-    let corpus = NgramData::<N>::new(corpus.chars());
+    let source = File::open(source).expect("failed to open corpus");
+    let mut corpus = NgramData::<N>::empty().with_normalization(normalization);
+    corpus
+        .add_reader(source, InvalidBytes::Error)
+        .expect("failed to read corpus");
     let data = File::create(data).expect("failed to create file for ngram data");
-    bincode::serialize_into(data, &corpus).expect("save ngram data to file");
+    corpus
+        .save(BufWriter::new(data), &info.expansion, info.source)
+        .expect("save ngram data to file");
     corpus
 }
 
 fn expansion(char: char) -> Vec<char> {
-    if char.is_ascii_uppercase() {
-        vec!['⇧', char.to_ascii_lowercase()]
-    } else if char == ' ' {
+    if char == ' ' {
         vec!['⎵']
     } else {
         vec![char]
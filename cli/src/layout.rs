@@ -1,6 +1,7 @@
+use algae_lib::{Finger, KeyPosition, PhysicalKey};
 use std::fmt::{self, Display, Formatter};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Key(pub [usize; 2]);
 
 #[derive(Clone, Debug)]
@@ -19,6 +20,29 @@ impl FromIterator<(Key, char)> for Layout {
     }
 }
 
+impl PhysicalKey for Key {
+    fn finger(&self) -> Finger {
+        let Key([_, col]) = self;
+        match col {
+            0 => Finger::LeftPinky,
+            1 => Finger::LeftRing,
+            2 => Finger::LeftMiddle,
+            3 | 4 => Finger::LeftIndex,
+            5 | 6 => Finger::RightIndex,
+            7 => Finger::RightMiddle,
+            8 => Finger::RightRing,
+            _ => Finger::RightPinky,
+        }
+    }
+}
+
+impl KeyPosition for Key {
+    fn position(&self) -> [f64; 2] {
+        let Key([row, col]) = self;
+        [*col as f64, *row as f64]
+    }
+}
+
 impl Display for Key {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let Key([row, col]) = self;
@@ -15,19 +15,46 @@ fn main() {
 }
 
 fn generate(corpus: &str, saved: &str) {
-    let bigrams = get_corpus::<2>(corpus, saved).ngrams::<2>();
+    let corpus = get_corpus::<2>(corpus, saved);
     println!("loaded the corpus");
-    let generator = Generator::new(
+    let charset: Vec<_> = ",./;".chars().chain('a'..='z').collect();
+    let options = GeneratorOptions {
+        modifiers: vec![shift()],
+        unknown_chars: UnknownChars::Warn,
+        max_dropped: Some(0.5),
+        ..GeneratorOptions::default()
+    };
+    let generator = Generator::with_options(
         (0..3).flat_map(|row| (0..10).map(move |col| Key([row, col]))),
-        ",./;".chars().chain('a'..='z'),
-        [bigrams],
+        charset,
+        [corpus.ngrams::<2>()],
         sfb_distance,
+        options,
     )
     .unwrap();
+    for (i, coverage) in generator.coverage().iter().enumerate() {
+        println!("Corpus {}: {coverage}", i + 1);
+    }
     println!("Generating layout optimized for sfb distance (taxicab).");
     let pins = [];
-    let layout: Layout = generator.generate(pins).unwrap();
+    let layout: Vec<_> = generator.generate(pins).unwrap();
+    let report = generator.report(layout.iter().copied()).unwrap();
+    let layout: Layout = layout.into_iter().collect();
     println!("{layout}");
+    print!("{report}");
+}
+
+// Capitals are typed with the shift on the opposite hand.
+fn shift() -> Modifier<Key> {
+    let [left, right] = [Key([3, 0]), Key([3, 9])];
+    Modifier::new(
+        [left, right],
+        ('a'..='z').map(|c| (c.to_ascii_uppercase(), c)),
+        move |key: &Key| match key.finger().hand() {
+            Hand::Left => right,
+            Hand::Right => left,
+        },
+    )
 }
 
 fn sfb_distance([Key([y1, x1]), Key([y2, x2])]: [Key; 2]) -> u64 {
@@ -28,6 +28,118 @@ where
     windows(v.iter().flat_map(expand))
 }
 
+/// Element with its neighbours. Neighbours are `None` at the start and
+/// the end of a document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Context<T> {
+    pub prev: Option<T>,
+    pub current: T,
+    pub next: Option<T>,
+}
+
+/// Expands a document start. Last element is used as context only.
+pub fn expand_start<T, R, Expansion, Iter, const N: usize, const M: usize>(
+    v: &[T; N],
+    expand: Expansion,
+) -> Vec<[R; M]>
+where
+    T: Copy,
+    R: Clone,
+    Expansion: Fn(Context<T>) -> Iter,
+    Iter: IntoIterator<Item = R>,
+{
+    assert!(M < N, "context expansion needs ngrams longer than {M}");
+    let expanded = expand_contexts(None, &v[..N - 1], Some(v[N - 1]), expand);
+    let first = expanded[0].len();
+    windows(expanded.into_iter().flatten())
+        .into_iter()
+        .take(first)
+        .collect()
+}
+
+/// Expands the second element of an ngram. First and last elements are
+/// used as context only.
+pub fn expand_inner<T, R, Expansion, Iter, const N: usize, const M: usize>(
+    v: &[T; N],
+    expand: Expansion,
+) -> Vec<[R; M]>
+where
+    T: Copy,
+    R: Clone,
+    Expansion: Fn(Context<T>) -> Iter,
+    Iter: IntoIterator<Item = R>,
+{
+    assert!(
+        M + 2 <= N,
+        "context expansion needs ngrams longer than {M} + 1"
+    );
+    let expanded = expand_contexts(Some(v[0]), &v[1..N - 1], Some(v[N - 1]), expand);
+    let first = expanded[0].len();
+    windows(expanded.into_iter().flatten())
+        .into_iter()
+        .take(first)
+        .collect()
+}
+
+/// Expands a document end. First element is used as context only.
+pub fn expand_end<T, R, Expansion, Iter, const N: usize, const M: usize>(
+    v: &[T; N],
+    expand: Expansion,
+) -> Vec<[R; M]>
+where
+    T: Copy,
+    R: Clone,
+    Expansion: Fn(Context<T>) -> Iter,
+    Iter: IntoIterator<Item = R>,
+{
+    assert!(M < N, "context expansion needs ngrams longer than {M}");
+    windows(
+        expand_contexts(Some(v[0]), &v[1..], None, expand)
+            .into_iter()
+            .flatten(),
+    )
+}
+
+/// Expands a whole document.
+pub fn expand_document<T, R, Expansion, Iter>(v: &[T], expand: Expansion) -> Vec<R>
+where
+    T: Copy,
+    Expansion: Fn(Context<T>) -> Iter,
+    Iter: IntoIterator<Item = R>,
+{
+    expand_contexts(None, v, None, expand)
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+// Expands every element of `v`, where `prev` and `next` are neighbours
+// of the whole slice.
+fn expand_contexts<T, R, Expansion, Iter>(
+    prev: Option<T>,
+    v: &[T],
+    next: Option<T>,
+    expand: Expansion,
+) -> Vec<Vec<R>>
+where
+    T: Copy,
+    Expansion: Fn(Context<T>) -> Iter,
+    Iter: IntoIterator<Item = R>,
+{
+    let expanded: Vec<Vec<R>> = (0..v.len())
+        .map(|i| Context {
+            prev: if i == 0 { prev } else { Some(v[i - 1]) },
+            current: v[i],
+            next: v.get(i + 1).copied().or(next),
+        })
+        .map(|context| expand(context).into_iter().collect())
+        .collect();
+    if expanded.iter().any(Vec::is_empty) {
+        panic!("each expansion must be at least 1 element long");
+    }
+    expanded
+}
+
 fn check_expansion<T, R, Expansion, Iter>(v: &[T], expand: &Expansion)
 where
     Expansion: Fn(&T) -> Iter,
@@ -59,14 +171,30 @@ mod tests {
     #[test]
     fn full_expansion() {
         let a = [(); 2];
-        let expanded = expand_full(&a, &expansion);
+        let expanded = expand_full(&a, expansion);
         assert_eq!(vec![[0, 1], [1, 2], [2, 0], [0, 1], [1, 2]], expanded);
     }
 
+    fn repeat(context: Context<char>) -> Vec<char> {
+        match context.prev == Some(context.current) {
+            true => vec!['@'],
+            false => vec![context.current],
+        }
+    }
+
+    #[test]
+    fn context_expansion() {
+        let v = ['a', 'a', 'a', 'b'];
+        assert_eq!(expand_start(&v, repeat), vec![['a', '@']]);
+        assert_eq!(expand_inner(&v, repeat), vec![['@', '@']]);
+        assert_eq!(expand_end(&v, repeat), vec![['@', '@'], ['@', 'b']]);
+        assert_eq!(expand_document(&v, repeat), vec!['a', '@', '@', 'b']);
+    }
+
     #[test]
     fn first_expansion() {
         let a = [(); 2];
-        let expanded = expand_first(&a, &expansion);
+        let expanded = expand_first(&a, expansion);
         assert_eq!(vec![[0, 1], [1, 2], [2, 0]], expanded);
     }
 }
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 #[derive(Clone, Debug)]
 pub struct CorpusSet<const N: usize> {
     ngrams: Vec<[usize; N]>,
@@ -16,24 +18,18 @@ impl<const N: usize> CorpusSet<N> {
         let mut ngrams = Vec::new();
         let mut weights = Vec::new();
         let mut weight_sums = vec![0; len];
+        // Positions of ngrams in `ngrams`.
+        let mut indices = HashMap::new();
         for (i, ngram, count) in corpora.into_iter().enumerate().flat_map(|(i, corpus)| {
             corpus
                 .into_iter()
                 .map(move |(ngram, count)| (i, ngram, count))
         }) {
-            let j = match ngrams
-                .iter_mut()
-                .enumerate()
-                .find(|(_, x)| **x == ngram)
-                .map(|(i, _)| i)
-            {
-                Some(j) => j,
-                None => {
-                    ngrams.push(ngram);
-                    weights.resize(weights.len() + len, 0);
-                    ngrams.len() - 1
-                }
-            };
+            let j = *indices.entry(ngram).or_insert_with(|| {
+                ngrams.push(ngram);
+                weights.resize(weights.len() + len, 0);
+                ngrams.len() - 1
+            });
             weights[j * len + i] = count;
             weight_sums[i] += count;
         }
@@ -50,6 +46,29 @@ impl<const N: usize> CorpusSet<N> {
         }
     }
 
+    /// Moves ngrams matching `f` into a new set. Both sets keep weight
+    /// sums of the whole set, so their scores can be added together.
+    pub fn split_off(&mut self, f: impl Fn(&[usize; N]) -> bool) -> Self {
+        let mut res = Self {
+            ngrams: Vec::new(),
+            weights: Vec::new(),
+            weight_sums: self.weight_sums.clone(),
+            corpus_count: self.corpus_count,
+        };
+        let mut kept = Self {
+            ngrams: Vec::new(),
+            weights: Vec::new(),
+            ..res.clone()
+        };
+        for (ngram, weights) in self.iter() {
+            let set = if f(ngram) { &mut res } else { &mut kept };
+            set.ngrams.push(*ngram);
+            set.weights.extend_from_slice(weights);
+        }
+        *self = kept;
+        res
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (&[usize; N], &[u64])> {
         self.ngrams
             .iter()
@@ -65,6 +84,17 @@ impl<const N: usize> CorpusSet<N> {
             .unwrap()
     }
 
+    /// Adds `extra` to the weight sum of every corpus.
+    pub fn add_weight_sums(&mut self, extra: &[u64]) {
+        for (sum, extra) in self.weight_sums.iter_mut().zip(extra) {
+            *sum += *extra as f64;
+        }
+    }
+
+    pub fn weight_sum(&self, corpus: usize) -> f64 {
+        self.weight_sums[corpus]
+    }
+
     pub fn len(&self) -> usize {
         self.corpus_count
     }
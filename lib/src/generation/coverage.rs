@@ -0,0 +1,255 @@
+use super::{CorpusSet, InvalidCorpora, LayoutEncoding};
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+/// What to do with ngrams containing chars which aren't in the charset
+/// or typed with a modifier.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownChars {
+    /// Fail to create the generator.
+    Error,
+    /// Drop the ngrams and show dropped ngrams in reports of every corpus
+    /// that lost any.
+    Warn,
+    /// Drop the ngrams.
+    #[default]
+    Drop,
+    /// Replace unknown chars with the given one. It has to be in the
+    /// charset, otherwise it is unknown too.
+    Fallback(char),
+}
+
+/// How much of a corpus was used by a generator.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CorpusCoverage {
+    /// Number of all ngrams.
+    pub total: u64,
+    /// Number of ngrams dropped because of unknown chars.
+    pub dropped: u64,
+    /// Number of ngrams with unknown chars replaced by the fallback.
+    pub replaced: u64,
+    /// Unknown chars with numbers of ngrams containing them, from the
+    /// most frequent.
+    pub unknown: Vec<(char, u64)>,
+}
+
+impl CorpusCoverage {
+    /// Fraction of ngrams that got dropped.
+    pub fn dropped_fraction(&self) -> f64 {
+        match self.total {
+            0 => 0.0,
+            total => self.dropped as f64 / total as f64,
+        }
+    }
+}
+
+impl Display for CorpusCoverage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "dropped {:.2}% of ngrams, replaced {:.2}%",
+            self.dropped_fraction() * 100.0,
+            self.replaced as f64 / self.total.max(1) as f64 * 100.0
+        )?;
+        let mut unknown = self.unknown.iter().take(5);
+        if let Some((char, _)) = unknown.next() {
+            write!(f, ", most frequent unknown chars: {char:?}")?;
+            for (char, _) in unknown {
+                write!(f, ", {char:?}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl UnknownChars {
+    // Encodes ngram, replacing unknown chars with the fallback. Returns
+    // whether any char got replaced.
+    pub(super) fn encode<K, const N: usize>(
+        self,
+        encoding: &LayoutEncoding<K>,
+        ngram: &[char; N],
+    ) -> Option<([usize; N], bool)> {
+        if let Some(ngram) = encoding.chars.encode_ngram(ngram) {
+            return Some((ngram, false));
+        }
+        let UnknownChars::Fallback(fallback) = self else {
+            return None;
+        };
+        let fallback = encoding.chars.encode(&fallback)?;
+        let ngram = ngram.map(|char| encoding.chars.encode(&char).unwrap_or(fallback));
+        Some((ngram, true))
+    }
+}
+
+// Encodes corpora, calculating their coverage.
+pub(super) fn encode_corpora<K, I, const N: usize>(
+    encoding: &LayoutEncoding<K>,
+    unknown_chars: UnknownChars,
+    corpora: impl IntoIterator<Item = I>,
+) -> (CorpusSet<N>, Vec<CorpusCoverage>)
+where
+    I: IntoIterator<Item = ([char; N], u64)>,
+{
+    let mut coverage = Vec::new();
+    let mut encoded = Vec::new();
+    for corpus in corpora {
+        let mut res = CorpusCoverage::default();
+        let mut unknown = HashMap::new();
+        let mut ngrams = Vec::new();
+        for (ngram, count) in corpus {
+            res.total += count;
+            let encoded = unknown_chars.encode(encoding, &ngram);
+            if !matches!(encoded, Some((_, false))) {
+                let mut chars: Vec<_> = ngram
+                    .into_iter()
+                    .filter(|char| encoding.chars.encode(char).is_none())
+                    .collect();
+                chars.sort_unstable();
+                chars.dedup();
+                for char in chars {
+                    *unknown.entry(char).or_insert(0) += count;
+                }
+            }
+            match encoded {
+                Some((ngram, replaced)) => {
+                    if replaced {
+                        res.replaced += count;
+                    }
+                    ngrams.push((ngram, count));
+                }
+                None => res.dropped += count,
+            }
+        }
+        res.unknown = unknown.into_iter().collect();
+        res.unknown
+            .sort_unstable_by(|(c1, n1), (c2, n2)| n2.cmp(n1).then(c1.cmp(c2)));
+        coverage.push(res);
+        encoded.push(merge_duplicates(ngrams));
+    }
+    (CorpusSet::new(encoded.into_iter()), coverage)
+}
+
+// Checks that corpora can be used with `unknown_chars` policy and at most
+// `max_dropped` fraction of their ngrams dropped.
+pub(super) fn check_coverage(
+    coverage: &[CorpusCoverage],
+    unknown_chars: UnknownChars,
+    max_dropped: Option<f64>,
+) -> Result<(), InvalidCorpora> {
+    if unknown_chars == UnknownChars::Error {
+        let mut unknown: Vec<_> = coverage
+            .iter()
+            .flat_map(|corpus| corpus.unknown.iter().map(|&(char, _)| char))
+            .collect();
+        unknown.sort_unstable();
+        unknown.dedup();
+        if !unknown.is_empty() {
+            return Err(InvalidCorpora::UnknownChars(unknown));
+        }
+    }
+    let max_dropped = max_dropped.unwrap_or(1.0);
+    let too_many_dropped: Vec<_> = coverage
+        .iter()
+        .enumerate()
+        .map(|(i, corpus)| (i, corpus.dropped_fraction()))
+        .filter(|&(_, dropped)| dropped > max_dropped)
+        .collect();
+    match too_many_dropped.is_empty() {
+        true => Ok(()),
+        false => Err(InvalidCorpora::TooManyDropped(too_many_dropped)),
+    }
+}
+
+// Sums counts of ngrams which became the same after replacing chars.
+fn merge_duplicates<const N: usize>(ngrams: Vec<([usize; N], u64)>) -> Vec<([usize; N], u64)> {
+    let mut res: HashMap<[usize; N], u64> = HashMap::with_capacity(ngrams.len());
+    for (ngram, count) in ngrams {
+        *res.entry(ngram).or_insert(0) += count;
+    }
+    res.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Generator, GeneratorOptions};
+
+    fn coverage(unknown_chars: UnknownChars) -> Vec<CorpusCoverage> {
+        let encoding = LayoutEncoding::new(vec![0, 1], vec!['a', 'b'], &[]).unwrap();
+        let corpora = [
+            vec![(['a', 'b'], 6), (['b', 'é'], 3), (['ü', 'é'], 1)],
+            vec![(['a', 'a'], 1)],
+        ];
+        encode_corpora(&encoding, unknown_chars, corpora).1
+    }
+
+    #[test]
+    pub fn corpus_coverage() {
+        let dropped = coverage(UnknownChars::Drop);
+        assert_eq!(dropped[0].total, 10);
+        assert_eq!((dropped[0].dropped, dropped[0].replaced), (4, 0));
+        assert_eq!(dropped[0].unknown, [('é', 4), ('ü', 1)]);
+        assert_eq!(dropped[0].dropped_fraction(), 0.4);
+        assert_eq!(
+            dropped[1],
+            CorpusCoverage {
+                total: 1,
+                ..CorpusCoverage::default()
+            }
+        );
+        let replaced = coverage(UnknownChars::Fallback('b'));
+        assert_eq!((replaced[0].dropped, replaced[0].replaced), (0, 4));
+        assert_eq!(replaced[0].unknown, dropped[0].unknown);
+        // Fallback outside of the charset is unknown too.
+        let unknown = coverage(UnknownChars::Fallback('c'));
+        assert_eq!((unknown[0].dropped, unknown[0].replaced), (4, 0));
+    }
+
+    #[test]
+    pub fn checks() {
+        let dropped = coverage(UnknownChars::Drop);
+        assert_eq!(check_coverage(&dropped, UnknownChars::Drop, None), Ok(()));
+        assert_eq!(
+            check_coverage(&dropped, UnknownChars::Warn, Some(0.4)),
+            Ok(())
+        );
+        assert_eq!(
+            check_coverage(&dropped, UnknownChars::Drop, Some(0.3)),
+            Err(InvalidCorpora::TooManyDropped(vec![(0, 0.4)]))
+        );
+        assert_eq!(
+            check_coverage(&dropped, UnknownChars::Error, None),
+            Err(InvalidCorpora::UnknownChars(vec!['é', 'ü']))
+        );
+        let replaced = coverage(UnknownChars::Fallback('b'));
+        assert_eq!(
+            check_coverage(&replaced, UnknownChars::Fallback('b'), Some(0.0)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    pub fn skipgrams() {
+        let metric = |[k1, k2]: [usize; 2]| (k1 == k2) as u64;
+        let generator = |unknown_chars| {
+            let options = GeneratorOptions {
+                unknown_chars,
+                max_dropped: Some(0.3),
+                ..GeneratorOptions::default()
+            };
+            Generator::with_options(0..2, ['a', 'b'], [[(['a', 'b'], 1)]], metric, options).unwrap()
+        };
+        let skipgrams = || [[vec![(['a', 'é'], 1), (['a', 'b'], 1)]]];
+        let result = generator(UnknownChars::Drop).with_skipgrams(skipgrams(), None, metric);
+        assert_eq!(
+            result.err(),
+            Some(InvalidCorpora::TooManyDropped(vec![(0, 0.5)]))
+        );
+        let result = generator(UnknownChars::Error).with_skipgrams(skipgrams(), None, metric);
+        assert_eq!(result.err(), Some(InvalidCorpora::UnknownChars(vec!['é'])));
+        let result =
+            generator(UnknownChars::Fallback('b')).with_skipgrams(skipgrams(), None, metric);
+        assert!(result.is_ok());
+    }
+}
@@ -1,41 +1,45 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
 #[derive(Clone, Debug)]
-pub struct Encoding<T>(pub Box<[T]>);
+pub struct Encoding<T> {
+    values: Box<[T]>,
+    indices: HashMap<T, usize>,
+}
 
 impl<T> Encoding<T>
 where
-    T: Clone + Eq,
+    T: Clone + Eq + Hash,
 {
-    pub fn new(mut values: Vec<T>) -> Result<Self, Vec<T>> {
-        let mut duplicates = 0;
-        for i in 1..values.len() {
-            if values[duplicates..i].contains(&values[i]) {
-                values.swap(duplicates, i);
-                duplicates += 1;
+    pub fn new(values: Vec<T>) -> Result<Self, Vec<T>> {
+        let mut indices = HashMap::with_capacity(values.len());
+        let mut duplicates = Vec::new();
+        for (i, value) in values.iter().enumerate() {
+            if indices.contains_key(value) {
+                duplicates.push(value.clone());
+            } else {
+                indices.insert(value.clone(), i);
             }
         }
-        match duplicates {
-            0 => Ok(Self(values.into_boxed_slice())),
-            _ => {
-                values.truncate(duplicates);
-                Err(values)
-            }
+        match duplicates.is_empty() {
+            true => Ok(Self {
+                values: values.into_boxed_slice(),
+                indices,
+            }),
+            false => Err(duplicates),
         }
     }
 
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.values.len()
     }
 
     pub fn encode(&self, v: &T) -> Option<usize> {
-        self.0
-            .iter()
-            .enumerate()
-            .find(|(_, w)| *w == v)
-            .map(|(i, _)| i)
+        self.indices.get(v).copied()
     }
 
     pub fn decode(&self, e: usize) -> &T {
-        &self.0[e]
+        &self.values[e]
     }
 
     pub fn encode_ngram<const N: usize>(&self, ngram: &[T; N]) -> Option<[usize; N]> {
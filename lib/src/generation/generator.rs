@@ -1,53 +1,277 @@
-use super::{CorpusSet, InvalidLayoutEncoding, LayoutEncoding, Metric};
+use super::coverage::{check_coverage, encode_corpora};
+use super::{
+    CorpusCoverage, CorpusReport, CorpusSet, InvalidLayoutEncoding, LayoutEncoding, Metric,
+    Modifier, Modifiers, Report, ScoreInterval, UnknownChars,
+};
+use crate::{Blocks, Pattern, PhysicalKey};
+use std::collections::HashMap;
+use std::hash::Hash;
 
 pub struct Generator<K, const N: usize> {
     encoding: LayoutEncoding<K>,
     corpus_set: CorpusSet<N>,
     metric: Metric<N>,
+    // Ngrams containing modified chars, which are scored by the keys
+    // pressed to type them.
+    modified: CorpusSet<N>,
+    skipgrams: Option<(CorpusSet<2>, Metric<2>)>,
+    coverage: Vec<CorpusCoverage>,
+    unknown_chars: UnknownChars,
+    max_dropped: Option<f64>,
+    dropped_penalty: Option<u64>,
+}
+
+/// Optional settings of a [`Generator`].
+pub struct GeneratorOptions<K> {
+    /// Modifiers used to type chars which aren't on the layout. Their
+    /// keys aren't assigned chars, but are passed to the metric.
+    pub modifiers: Vec<Modifier<K>>,
+    /// What to do with ngrams containing chars which can't be typed.
+    /// Applies to skipgrams too.
+    pub unknown_chars: UnknownChars,
+    /// Creating the generator or adding skipgrams fails if any corpus
+    /// loses more than this fraction of ngrams because of unknown chars.
+    pub max_dropped: Option<f64>,
+    /// Weight of ngrams containing chars left without a key, when there
+    /// are more chars than keys. Defaults to the highest weight given by
+    /// the metric, or the skipgram metric for skipgrams.
+    pub dropped_penalty: Option<u64>,
+}
+
+impl<K> Default for GeneratorOptions<K> {
+    fn default() -> Self {
+        Self {
+            modifiers: Vec::new(),
+            unknown_chars: UnknownChars::default(),
+            max_dropped: None,
+            dropped_penalty: None,
+        }
+    }
 }
 
 impl<K, const N: usize> Generator<K, N>
 where
-    K: Clone + Eq,
+    K: Clone + Eq + Hash,
 {
+    /// Creates a generator placing chars of `charset` on keys of
+    /// `keyset`. Layouts are scored with `metric` on every corpus and
+    /// the worst score is minimized.
+    ///
+    /// Numbers of keys and chars can differ. Keys without chars are left
+    /// blank. When there are more chars than keys, the generator chooses
+    /// which chars are dropped and ngrams containing them weigh
+    /// [`GeneratorOptions::dropped_penalty`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::Generator;
+    /// let corpus = [(['a', 'b'], 1), (['b', 'c'], 5)];
+    /// let metric = |[k1, k2]: [usize; 2]| (k1 == k2) as u64;
+    /// let generator = Generator::new(0..2, ['a', 'b', 'c'], [corpus], metric).unwrap();
+    /// let layout: Vec<_> = generator.generate([]).unwrap();
+    /// assert_eq!(layout.len(), 2);
+    /// assert!(layout.iter().all(|&(_, char)| char != 'a'));
+    /// ```
     pub fn new<I>(
         keyset: impl IntoIterator<Item = K>,
         charset: impl IntoIterator<Item = char>,
         corpora: impl IntoIterator<Item = I>,
         metric: impl Fn([K; N]) -> u64,
-    ) -> Result<Self, InvalidLayoutEncoding<K>>
+    ) -> Result<Self, GeneratorError<K>>
     where
         I: IntoIterator<Item = ([char; N], u64)>,
     {
-        let encoding =
-            LayoutEncoding::new(keyset.into_iter().collect(), charset.into_iter().collect())?;
-        let corpora = corpora.into_iter().map(|corpus| {
-            corpus.into_iter().flat_map(|(ngram, count)| {
-                encoding
-                    .chars
-                    .encode_ngram(&ngram)
-                    .map(|ngram| (ngram, count))
-            })
-        });
-        let corpus_set = CorpusSet::new(corpora);
-        let metric = Metric::new(encoding.len(), |nstroke| {
-            metric(encoding.keys.decode_ngram(nstroke))
-        });
+        Self::with_options(
+            keyset,
+            charset,
+            corpora,
+            metric,
+            GeneratorOptions::default(),
+        )
+    }
+
+    /// Creates a generator like [`Generator::new`] with additional
+    /// `options`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if keys, chars or modifiers are invalid, or if
+    /// corpora can't be used because of unknown chars, see
+    /// [`GeneratorOptions::unknown_chars`] and
+    /// [`GeneratorOptions::max_dropped`].
+    ///
+    /// Ngrams containing chars typed with a modifier are scored by keys
+    /// pressed to type them. Their windows of `N` keys starting in the
+    /// keys of the first char are passed to `metric`, so it's charged
+    /// for the modifier keys chosen for the current layout. Scores are
+    /// averaged over these windows, so an ngram starting with a modified
+    /// char counts as 2 ngrams, like the 2 keystrokes needed for its
+    /// first char.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{Generator, GeneratorOptions, Modifier};
+    /// let shift = Modifier::new([2, 3], [('A', 'a')], |&key: &usize| 3 - key);
+    /// let options = GeneratorOptions {
+    ///     modifiers: vec![shift],
+    ///     ..GeneratorOptions::default()
+    /// };
+    /// let corpus = [(['b', 'A'], 1)];
+    /// // Pressing the shift on key 3 is costly.
+    /// let metric = |[_, k2]: [usize; 2]| (k2 == 3) as u64;
+    /// let generator = Generator::with_options(0..2, ['a', 'b'], [corpus], metric, options);
+    /// let layout: Vec<_> = generator.unwrap().generate([]).unwrap();
+    /// assert!(layout.contains(&(1, 'a')));
+    /// ```
+    pub fn with_options<I>(
+        keyset: impl IntoIterator<Item = K>,
+        charset: impl IntoIterator<Item = char>,
+        corpora: impl IntoIterator<Item = I>,
+        metric: impl Fn([K; N]) -> u64,
+        options: GeneratorOptions<K>,
+    ) -> Result<Self, GeneratorError<K>>
+    where
+        I: IntoIterator<Item = ([char; N], u64)>,
+    {
+        let encoding = LayoutEncoding::new(
+            keyset.into_iter().collect(),
+            charset.into_iter().collect(),
+            &options.modifiers,
+        )?;
+        let (mut corpus_set, coverage) = encode_corpora(&encoding, options.unknown_chars, corpora);
+        check_coverage(&coverage, options.unknown_chars, options.max_dropped)?;
+        let mut modified =
+            corpus_set.split_off(|ngram| ngram.iter().any(|&c| encoding.modifiers.is_modified(c)));
+        // Every window of keys beyond the first one is scored as a
+        // separate ngram.
+        let mut extra_windows = vec![0; modified.len()];
+        for (ngram, counts) in modified.iter() {
+            let windows = encoding.modifiers.windows(ngram);
+            for (extra, count) in extra_windows.iter_mut().zip(counts) {
+                *extra += count * (windows - 1);
+            }
+        }
+        corpus_set.add_weight_sums(&extra_windows);
+        modified.add_weight_sums(&extra_windows);
+        let metric = Metric::new(
+            encoding.keys.len(),
+            encoding.keys_len(),
+            options.dropped_penalty,
+            |nstroke| metric(encoding.keys.decode_ngram(nstroke)),
+        );
         Ok(Self {
             encoding,
             corpus_set,
             metric,
+            modified,
+            skipgrams: None,
+            coverage,
+            unknown_chars: options.unknown_chars,
+            max_dropped: options.max_dropped,
+            dropped_penalty: options.dropped_penalty,
         })
     }
 
+    /// Adds skipgrams to scoring of the layouts.
+    ///
+    /// `corpora` has to contain skipgram tables of corpora used to create
+    /// the generator in the same order. Tables of each corpus are ordered
+    /// by gap size starting from 1, like the ones returned by
+    /// [`NgramData::skipgram_tables`](crate::NgramData::skipgram_tables).
+    /// Counts of skipgrams with gap `g` are multiplied by `decay[g - 1]`
+    /// (or `1.0` if there is no such weight). Their score gets added to
+    /// the score of their corpus. Modified chars of skipgrams are scored
+    /// as their base chars. Unknown chars are handled like in ngrams, see
+    /// [`GeneratorOptions::unknown_chars`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any weight of `decay` is negative or not
+    /// finite, if number of skipgram corpora is different than number of
+    /// corpora given to the generator, or if they can't be used because
+    /// of unknown chars, like the corpora of the generator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{Generator, InvalidCorpora, NgramData};
+    /// let corpus = NgramData::<3>::new("abcab".chars());
+    /// let metric = |[k1, k2]: [usize; 2]| (k1 == k2) as u64;
+    /// let generator = Generator::new(0..3, ['a', 'b', 'c'], [corpus.ngrams::<2>()], metric);
+    /// let generator = generator.unwrap();
+    /// let skipgrams = [corpus.skipgram_tables()];
+    /// let generator = generator.with_skipgrams(skipgrams.clone(), None, metric).unwrap();
+    /// let corpora = [skipgrams[0].clone(), skipgrams[0].clone()];
+    /// assert_eq!(
+    ///     generator.with_skipgrams(corpora, Some(&[0.5]), metric).err(),
+    ///     Some(InvalidCorpora::DifferentCount { expected: 1, found: 2 }),
+    /// );
+    /// ```
+    pub fn with_skipgrams<C, I>(
+        mut self,
+        corpora: impl IntoIterator<Item = C>,
+        decay: Option<&[f64]>,
+        metric: impl Fn([K; 2]) -> u64,
+    ) -> Result<Self, InvalidCorpora>
+    where
+        C: IntoIterator<Item = I>,
+        I: IntoIterator<Item = ([char; 2], u64)>,
+    {
+        let decay = decay.unwrap_or(&[]);
+        if let Some(i) = decay
+            .iter()
+            .position(|decay| !decay.is_finite() || *decay < 0.0)
+        {
+            return Err(InvalidCorpora::InvalidDecay {
+                gap: i + 1,
+                decay: decay[i],
+            });
+        }
+        let corpora = corpora.into_iter().map(|tables| {
+            let mut skipgrams = HashMap::new();
+            for (i, table) in tables.into_iter().enumerate() {
+                let decay = decay.get(i).copied().unwrap_or(1.0);
+                for (skipgram, count) in table {
+                    let skipgram = skipgram.map(|c| self.encoding.base(c));
+                    *skipgrams.entry(skipgram).or_insert(0) +=
+                        (count as f64 * decay).round() as u64;
+                }
+            }
+            skipgrams
+        });
+        let (corpus_set, coverage) = encode_corpora(&self.encoding, self.unknown_chars, corpora);
+        if corpus_set.len() != self.corpus_set.len() {
+            return Err(InvalidCorpora::DifferentCount {
+                expected: self.corpus_set.len(),
+                found: corpus_set.len(),
+            });
+        }
+        check_coverage(&coverage, self.unknown_chars, self.max_dropped)?;
+        let metric = Metric::new(
+            self.encoding.keys.len(),
+            self.encoding.keys_len(),
+            self.dropped_penalty,
+            |nstroke| metric(self.encoding.keys.decode_ngram(nstroke)),
+        );
+        self.skipgrams = Some((corpus_set, metric));
+        Ok(self)
+    }
+
     fn full_analysis(&self, layout: &[usize], buffer: &mut [u64]) -> f64 {
         debug_assert_eq!(buffer.len(), self.corpus_set.len());
         buffer.fill(0);
-        for (ngram, counts) in self.corpus_set.iter() {
-            let weight = self.metric.weight(ngram, layout);
-            for (i, count) in counts.iter().enumerate() {
-                buffer[i] += count * weight;
-            }
+        add_scores(&self.corpus_set, &self.metric, layout, buffer);
+        add_modified_scores(
+            &self.modified,
+            &self.metric,
+            &self.encoding.modifiers,
+            layout,
+            buffer,
+        );
+        if let Some((corpus_set, metric)) = &self.skipgrams {
+            add_scores(corpus_set, metric, layout, buffer);
         }
         self.corpus_set.aggregate_scores(buffer)
     }
@@ -88,10 +312,12 @@ where
                 self.encoding
                     .keys
                     .encode(&key)
+                    .filter(|&k| k < self.encoding.keys_len())
                     .ok_or(InvalidPin::InvalidKey(key.clone()))?,
                 self.encoding
                     .chars
                     .encode(&char)
+                    .filter(|&c| c < self.encoding.chars_len())
                     .ok_or(InvalidPin::InvalidChar(char))?,
             );
             let i = layout
@@ -121,14 +347,254 @@ where
         };
         Ok(self.encoding.decode(layout).collect())
     }
+
+    /// Analyzes `layout` on every corpus. Besides the score it reports
+    /// how often each [`Pattern`] occurs. Patterns of ngrams containing
+    /// modified chars are counted for every window of keys scored, which
+    /// are also counted in the total, so percentages of patterns add up
+    /// to at most 100%.
+    pub fn report(
+        &self,
+        layout: impl IntoIterator<Item = (K, char)>,
+    ) -> Result<Report, InvalidPin<K>>
+    where
+        K: PhysicalKey,
+    {
+        let layout = self.encoding.encode(layout)?;
+        let len = self.corpus_set.len();
+        let mut scores = vec![0; len];
+        let mut patterns = vec![[0; Pattern::ALL.len()]; len];
+        for (ngram, counts) in self.corpus_set.iter() {
+            let weight = self.metric.weight(ngram, &layout);
+            // Ngrams with dropped chars don't follow any pattern.
+            let pattern = match ngram.iter().all(|&c| layout[c] < self.encoding.keys_len()) {
+                true => Pattern::of(&ngram.map(|c| self.encoding.keys.decode(layout[c]))),
+                false => None,
+            };
+            for (i, &count) in counts.iter().enumerate() {
+                scores[i] += count * weight;
+                if let Some(pattern) = pattern {
+                    patterns[i][pattern as usize] += count;
+                }
+            }
+        }
+        let mut keystrokes = Vec::new();
+        for (ngram, counts) in self.modified.iter() {
+            let modifiers = &self.encoding.modifiers;
+            let typed = modifiers.for_each_window(ngram, &layout, &mut keystrokes, |nstroke| {
+                let weight = self.metric.key_weight(nstroke);
+                let keys = nstroke.map(|k| self.encoding.keys.decode(k));
+                let pattern = Pattern::of(&keys);
+                for (i, &count) in counts.iter().enumerate() {
+                    scores[i] += count * weight;
+                    if let Some(pattern) = pattern {
+                        patterns[i][pattern as usize] += count;
+                    }
+                }
+            });
+            if !typed {
+                let penalty = self.metric.penalty() * modifiers.windows(ngram);
+                for (score, count) in scores.iter_mut().zip(counts) {
+                    *score += count * penalty;
+                }
+            }
+        }
+        if let Some((corpus_set, metric)) = &self.skipgrams {
+            add_scores(corpus_set, metric, &layout, &mut scores);
+        }
+        let corpora = scores
+            .into_iter()
+            .zip(patterns)
+            .enumerate()
+            .map(|(i, (score, patterns))| {
+                let total = self.corpus_set.weight_sum(i);
+                let coverage = &self.coverage[i];
+                let warning = (self.unknown_chars == UnknownChars::Warn && coverage.dropped > 0)
+                    .then(|| coverage.clone());
+                CorpusReport::new(score, total, patterns, warning)
+            })
+            .collect();
+        Ok(Report::new(corpora))
+    }
+
+    /// Coverage of corpora given to the generator, in the same order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{Generator, GeneratorOptions, UnknownChars};
+    /// let corpus = [(['a', 'b'], 3), (['b', 'é'], 1)];
+    /// let metric = |_: [usize; 2]| 0;
+    /// let options = GeneratorOptions {
+    ///     unknown_chars: UnknownChars::Fallback('b'),
+    ///     ..GeneratorOptions::default()
+    /// };
+    /// let generator = Generator::with_options(0..2, ['a', 'b'], [corpus], metric, options).unwrap();
+    /// let coverage = &generator.coverage()[0];
+    /// assert_eq!((coverage.replaced, coverage.dropped), (1, 0));
+    /// assert_eq!(coverage.unknown, [('é', 1)]);
+    ///
+    /// let options = GeneratorOptions {
+    ///     max_dropped: Some(0.1),
+    ///     ..GeneratorOptions::default()
+    /// };
+    /// assert!(Generator::with_options(0..2, ['a', 'b'], [corpus], metric, options).is_err());
+    /// ```
+    pub fn coverage(&self) -> &[CorpusCoverage] {
+        &self.coverage
+    }
+
+    /// Scores `layout` on `corpus` like on corpora of the generator.
+    /// Skipgrams aren't scored, as they are given for corpora of the
+    /// generator only.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::Generator;
+    /// let metric = |[k1, k2]: [usize; 2]| (k1 == k2) as u64;
+    /// let generator = Generator::new(0..2, ['a', 'b'], [[(['a', 'b'], 1)]], metric).unwrap();
+    /// let corpus = [(['a', 'a'], 1), (['a', 'b'], 3)];
+    /// assert_eq!(generator.score([(0, 'a'), (1, 'b')], corpus).unwrap(), 0.25);
+    /// ```
+    pub fn score(
+        &self,
+        layout: impl IntoIterator<Item = (K, char)>,
+        corpus: impl IntoIterator<Item = ([char; N], u64)>,
+    ) -> Result<f64, InvalidPin<K>> {
+        let layout = self.encoding.encode(layout)?;
+        Ok(self.corpus_score(&layout, corpus))
+    }
+
+    /// Scores `layout` on `resamples` resampled corpora of each of
+    /// `corpora` (see [`Blocks::resamples`]). Returns intervals containing
+    /// `confidence` fraction of the scores for every corpus, so it can be
+    /// told whether layouts differ more than the corpora themselves.
+    /// Scores are calculated with [`Generator::score`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `resamples` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{Block, Generator, NgramData};
+    /// let documents = ["abab", "aabb", "ab", "ba"];
+    /// let blocks = NgramData::<2>::empty().blocks(documents.map(str::chars), Block::Document);
+    /// let metric = |[k1, k2]: [usize; 2]| (k1 == k2) as u64;
+    /// let corpus = blocks.corpus().ngrams::<2>();
+    /// let generator = Generator::new(0..2, ['a', 'b'], [corpus], metric).unwrap();
+    /// let layout = [(0, 'a'), (1, 'b')];
+    /// let intervals = generator.bootstrap(layout, &[blocks], 100, 0.95, 0).unwrap();
+    /// assert!(intervals[0].lower <= intervals[0].mean);
+    /// assert!(intervals[0].mean <= intervals[0].upper);
+    /// ```
+    pub fn bootstrap(
+        &self,
+        layout: impl IntoIterator<Item = (K, char)>,
+        corpora: &[Blocks<N>],
+        resamples: usize,
+        confidence: f64,
+        seed: u64,
+    ) -> Result<Vec<ScoreInterval>, InvalidPin<K>> {
+        assert!(resamples > 0, "at least one resample is needed");
+        let layout = self.encoding.encode(layout)?;
+        let intervals = corpora
+            .iter()
+            .enumerate()
+            .map(|(i, blocks)| {
+                let scores = blocks
+                    .resamples(seed.wrapping_add(i as u64))
+                    .take(resamples)
+                    .map(|corpus| self.corpus_score(&layout, corpus.ngrams::<N>()))
+                    .collect();
+                ScoreInterval::new(scores, confidence)
+            })
+            .collect();
+        Ok(intervals)
+    }
+
+    fn corpus_score(
+        &self,
+        layout: &[usize],
+        corpus: impl IntoIterator<Item = ([char; N], u64)>,
+    ) -> f64 {
+        let modifiers = &self.encoding.modifiers;
+        let mut keystrokes = Vec::new();
+        let (mut score, mut total) = (0, 0);
+        for (ngram, count) in corpus {
+            let Some((ngram, _)) = self.unknown_chars.encode(&self.encoding, &ngram) else {
+                continue;
+            };
+            let windows = modifiers.windows(&ngram);
+            total += count * windows;
+            match ngram.iter().any(|&c| modifiers.is_modified(c)) {
+                true => {
+                    let typed =
+                        modifiers.for_each_window(&ngram, layout, &mut keystrokes, |nstroke| {
+                            score += count * self.metric.key_weight(nstroke);
+                        });
+                    if !typed {
+                        score += count * windows * self.metric.penalty();
+                    }
+                }
+                false => score += count * self.metric.weight(&ngram, layout),
+            }
+        }
+        score as f64 / total.max(1) as f64
+    }
 }
 
-#[derive(Debug)]
+fn add_scores<const N: usize>(
+    corpus_set: &CorpusSet<N>,
+    metric: &Metric<N>,
+    layout: &[usize],
+    buffer: &mut [u64],
+) {
+    for (ngram, counts) in corpus_set.iter() {
+        let weight = metric.weight(ngram, layout);
+        for (score, count) in buffer.iter_mut().zip(counts) {
+            *score += count * weight;
+        }
+    }
+}
+
+fn add_modified_scores<const N: usize>(
+    corpus_set: &CorpusSet<N>,
+    metric: &Metric<N>,
+    modifiers: &Modifiers,
+    layout: &[usize],
+    buffer: &mut [u64],
+) {
+    let mut keystrokes = Vec::new();
+    for (ngram, counts) in corpus_set.iter() {
+        let typed = modifiers.for_each_window(ngram, layout, &mut keystrokes, |nstroke| {
+            let weight = metric.key_weight(nstroke);
+            for (score, count) in buffer.iter_mut().zip(counts) {
+                *score += count * weight;
+            }
+        });
+        if !typed {
+            let penalty = metric.penalty() * modifiers.windows(ngram);
+            for (score, count) in buffer.iter_mut().zip(counts) {
+                *score += count * penalty;
+            }
+        }
+    }
+}
+
+/// Invalid pins given to [`Generator::generate`] or invalid layout given
+/// to [`Generator::report`].
+#[derive(Clone, Debug)]
 pub enum InvalidPin<K> {
     InvalidKey(K),
     DuplicateKey(K),
     InvalidChar(char),
     DuplicateChar(char),
+    /// Char of the charset isn't on the layout, while there are no keys
+    /// left for it to be dropped. Only whole layouts can miss chars.
+    MissingChar(char),
 }
 
 use std::fmt::{self, Display, Formatter};
@@ -140,6 +606,135 @@ impl<T: Display> Display for InvalidPin<T> {
             DuplicateKey(key) => write!(f, "duplicate key ({key})"),
             InvalidChar(char) => write!(f, "invalid char ({char})"),
             DuplicateChar(char) => write!(f, "duplicate char ({char})"),
+            MissingChar(char) => write!(f, "missing char ({char})"),
+        }
+    }
+}
+
+impl<K: Display + fmt::Debug> std::error::Error for InvalidPin<K> {}
+
+/// Corpora which can't be used by a [`Generator`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum InvalidCorpora {
+    /// Number of corpora is different than number of corpora given to
+    /// the generator.
+    DifferentCount { expected: usize, found: usize },
+    /// Weight of skipgrams with given gap is negative or not finite.
+    InvalidDecay { gap: usize, decay: f64 },
+    /// Chars of corpora missing from the charset, with
+    /// [`UnknownChars::Error`] policy.
+    UnknownChars(Vec<char>),
+    /// Indices of corpora which lost more ngrams than
+    /// [`GeneratorOptions::max_dropped`], with the fraction they lost.
+    TooManyDropped(Vec<(usize, f64)>),
+}
+
+impl Display for InvalidCorpora {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidCorpora::DifferentCount { expected, found } => {
+                write!(f, "expected {expected} corpora, found {found}")
+            }
+            InvalidCorpora::InvalidDecay { gap, decay } => {
+                write!(f, "invalid decay of skipgrams with gap {gap} ({decay})")
+            }
+            InvalidCorpora::UnknownChars(chars) => {
+                write!(f, "chars of corpora missing from the charset: {chars:?}")
+            }
+            InvalidCorpora::TooManyDropped(corpora) => {
+                write!(f, "too many ngrams lost because of unknown chars")?;
+                for (i, (corpus, dropped)) in corpora.iter().enumerate() {
+                    let separator = if i == 0 { ":" } else { "," };
+                    write!(
+                        f,
+                        "{separator} {:.2}% in corpus {}",
+                        dropped * 100.0,
+                        corpus + 1
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvalidCorpora {}
+
+/// Reason why a [`Generator`] couldn't be created.
+#[derive(Clone, Debug)]
+pub enum GeneratorError<K> {
+    Layout(InvalidLayoutEncoding<K>),
+    Corpora(InvalidCorpora),
+}
+
+impl<K> From<InvalidLayoutEncoding<K>> for GeneratorError<K> {
+    fn from(error: InvalidLayoutEncoding<K>) -> Self {
+        GeneratorError::Layout(error)
+    }
+}
+
+impl<K> From<InvalidCorpora> for GeneratorError<K> {
+    fn from(error: InvalidCorpora) -> Self {
+        GeneratorError::Corpora(error)
+    }
+}
+
+impl<K: Display> Display for GeneratorError<K> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GeneratorError::Layout(error) => write!(f, "{error}"),
+            GeneratorError::Corpora(error) => writeln!(f, "Invalid corpora: {error}."),
+        }
+    }
+}
+
+impl<K: Display + fmt::Debug> std::error::Error for GeneratorError<K> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn dropped_penalty() {
+        let generate = |dropped_penalty| {
+            let options = GeneratorOptions {
+                dropped_penalty,
+                ..GeneratorOptions::default()
+            };
+            let metric = |_: [usize; 2]| 1;
+            let corpus = [(['a', 'b'], 1)];
+            let generator =
+                Generator::with_options(0..2, ['a', 'b', 'c'], [corpus], metric, options);
+            // Skipgrams on different keys weigh 3, out of at most 5.
+            let metric = |[k1, k2]: [usize; 2]| if k1 == k2 { 5 } else { 3 };
+            let skipgrams = [[[(['a', 'b'], 10)]]];
+            let generator = generator.unwrap().with_skipgrams(skipgrams, None, metric);
+            let layout: Vec<_> = generator.unwrap().generate([]).unwrap();
+            let mut chars: Vec<_> = layout.into_iter().map(|(_, char)| char).collect();
+            chars.sort_unstable();
+            chars
+        };
+        // Skipgrams with dropped chars weigh 5, so dropping them costs more.
+        assert_eq!(generate(None), ['a', 'b']);
+        assert_ne!(generate(Some(1)), ['a', 'b']);
+    }
+
+    #[test]
+    pub fn invalid_decay() {
+        let metric = |[k1, k2]: [usize; 2]| (k1 == k2) as u64;
+        let corpus = [(['a', 'b'], 1)];
+        let skipgrams = [[[(['a', 'b'], 1)], [(['b', 'a'], 1)]]];
+        for decay in [-0.5, f64::NAN, f64::INFINITY] {
+            let generator = Generator::new(0..2, ['a', 'b'], [corpus], metric).unwrap();
+            let result = generator.with_skipgrams(skipgrams, Some(&[1.0, decay]), metric);
+            assert!(matches!(
+                result.err(),
+                Some(InvalidCorpora::InvalidDecay { gap: 2, .. })
+            ));
         }
+        let generator = Generator::new(0..2, ['a', 'b'], [corpus], metric).unwrap();
+        assert!(generator
+            .with_skipgrams(skipgrams, Some(&[1.0, 0.0]), metric)
+            .is_ok());
     }
 }
@@ -1,45 +1,175 @@
-use super::Encoding;
+use super::{Encoding, InvalidPin, Modifier, Modifiers};
 use std::cmp::Eq;
 use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::Hash;
 
+// Layouts are permutations of `len` slots, where `len` is the larger of
+// numbers of keys and chars. Chars of slots past the charset are blank
+// and chars put on keys past the keyset are dropped.
 #[derive(Debug)]
 pub struct LayoutEncoding<K> {
+    // Keys and chars of modifiers are encoded after the ones which make
+    // up the layout.
     pub keys: Encoding<K>,
     pub chars: Encoding<char>,
+    pub modifiers: Modifiers,
+    keys_len: usize,
+    chars_len: usize,
 }
 
-impl<K: Clone + Eq> LayoutEncoding<K> {
-    pub fn new(keys: Vec<K>, chars: Vec<char>) -> Result<Self, InvalidLayoutEncoding<K>> {
-        let counts = match keys.len() == chars.len() {
-            true => None,
-            false => Some([keys.len(), chars.len()]),
+impl<K: Clone + Eq + Hash> LayoutEncoding<K> {
+    pub fn new(
+        keys: Vec<K>,
+        chars: Vec<char>,
+        modifiers: &[Modifier<K>],
+    ) -> Result<Self, InvalidLayoutEncoding<K>> {
+        let (keys_len, chars_len) = (keys.len(), chars.len());
+        let keys = keys
+            .into_iter()
+            .chain(modifiers.iter().flat_map(|m| m.keys.iter().cloned()))
+            .collect();
+        let chars = chars
+            .into_iter()
+            .chain(
+                modifiers
+                    .iter()
+                    .flat_map(|m| m.chars.iter().map(|&(c, _)| c)),
+            )
+            .collect();
+        let (keys, chars) = match (Encoding::new(keys), Encoding::new(chars)) {
+            (Ok(keys), Ok(chars)) => (keys, chars),
+            (keys, chars) => {
+                return Err(Problems {
+                    duplicate_keys: keys.err().unwrap_or(Vec::new()),
+                    duplicate_chars: chars.err().unwrap_or(Vec::new()),
+                    ..Problems::default()
+                }
+                .into())
+            }
         };
-        match (Encoding::new(keys), Encoding::new(chars), counts) {
-            (Ok(keys), Ok(chars), None) => Ok(Self { keys, chars }),
-            (keys, chars, different_counts) => Err(InvalidLayoutEncoding {
-                duplicate_keys: keys.err().unwrap_or(Vec::new()),
-                duplicate_chars: chars.err().unwrap_or(Vec::new()),
-                different_counts,
+        match Modifiers::new(&keys, &chars, keys_len, chars_len, modifiers) {
+            Ok(modifiers) => Ok(Self {
+                keys,
+                chars,
+                modifiers,
+                keys_len,
+                chars_len,
             }),
+            Err((invalid_base_chars, invalid_modifier_keys)) => Err(Problems {
+                invalid_base_chars,
+                invalid_modifier_keys,
+                ..Problems::default()
+            }
+            .into()),
+        }
+    }
+
+    /// Character on the layout used to type `char`.
+    pub fn base(&self, char: char) -> char {
+        match self.chars.encode(&char) {
+            Some(c) => *self.chars.decode(self.modifiers.base(c)),
+            None => char,
+        }
+    }
+
+    /// Encodes `layout`. Chars missing from it are dropped and keys
+    /// missing from it are left blank.
+    pub fn encode(
+        &self,
+        layout: impl IntoIterator<Item = (K, char)>,
+    ) -> Result<Vec<usize>, InvalidPin<K>> {
+        let mut res = vec![None; self.len()];
+        let mut used = vec![false; self.len()];
+        for (key, char) in layout {
+            let k = self
+                .keys
+                .encode(&key)
+                .filter(|&k| k < self.keys_len)
+                .ok_or(InvalidPin::InvalidKey(key.clone()))?;
+            let c = self
+                .chars
+                .encode(&char)
+                .filter(|&c| c < self.chars_len)
+                .ok_or(InvalidPin::InvalidChar(char))?;
+            if used[k] {
+                return Err(InvalidPin::DuplicateKey(key));
+            }
+            if res[c].is_some() {
+                return Err(InvalidPin::DuplicateChar(char));
+            }
+            used[k] = true;
+            res[c] = Some(k);
+        }
+        // Only as many chars can be dropped as there are chars without
+        // keys of the keyset.
+        let mut dropped = self.keys_len..self.len();
+        for (c, key) in res.iter_mut().enumerate().take(self.chars_len) {
+            if key.is_none() {
+                let k = dropped
+                    .next()
+                    .ok_or(InvalidPin::MissingChar(*self.chars.decode(c)))?;
+                used[k] = true;
+                *key = Some(k);
+            }
         }
+        let mut blank = (0..self.len()).filter(|&k| !used[k]);
+        Ok(res
+            .into_iter()
+            .map(|k| k.or_else(|| blank.next()).unwrap())
+            .collect())
     }
 
+    /// Decodes `vec`, skipping dropped chars and blank keys.
     pub fn decode(&self, vec: Vec<usize>) -> impl Iterator<Item = (K, char)> + '_ {
         vec.into_iter()
             .enumerate()
+            .filter(|&(c, k)| c < self.chars_len && k < self.keys_len)
             .map(|(c, k)| (self.keys.decode(k).clone(), *self.chars.decode(c)))
     }
 
+    /// Number of slots of the layout.
     pub fn len(&self) -> usize {
-        self.keys.len()
+        self.keys_len.max(self.chars_len)
+    }
+
+    /// Number of keys on the layout.
+    pub fn keys_len(&self) -> usize {
+        self.keys_len
+    }
+
+    /// Number of chars of the charset.
+    pub fn chars_len(&self) -> usize {
+        self.chars_len
     }
 }
 
+// Boxed, so results of creating generators stay small.
+#[derive(Clone, Debug)]
+pub struct InvalidLayoutEncoding<K>(Box<Problems<K>>);
+
 #[derive(Clone, Debug)]
-pub struct InvalidLayoutEncoding<K> {
+struct Problems<K> {
     duplicate_keys: Vec<K>,
     duplicate_chars: Vec<char>,
-    different_counts: Option<[usize; 2]>,
+    invalid_base_chars: Vec<char>,
+    invalid_modifier_keys: Vec<K>,
+}
+
+impl<K> Default for Problems<K> {
+    fn default() -> Self {
+        Self {
+            duplicate_keys: Vec::new(),
+            duplicate_chars: Vec::new(),
+            invalid_base_chars: Vec::new(),
+            invalid_modifier_keys: Vec::new(),
+        }
+    }
+}
+
+impl<K> From<Problems<K>> for InvalidLayoutEncoding<K> {
+    fn from(problems: Problems<K>) -> Self {
+        Self(Box::new(problems))
+    }
 }
 
 impl<K: Display> Display for InvalidLayoutEncoding<K> {
@@ -49,9 +179,12 @@ impl<K: Display> Display for InvalidLayoutEncoding<K> {
             vec: &[T],
             label: &str,
         ) -> fmt::Result {
+            print_list(f, vec, &format!("Duplicate {label}s"))
+        }
+        fn print_list<T: Display>(f: &mut Formatter<'_>, vec: &[T], label: &str) -> fmt::Result {
             let mut iter = vec.iter();
             if let Some(head) = iter.next() {
-                write!(f, "Duplicate {label}s: {head}")?;
+                write!(f, "{label}: {head}")?;
                 for value in iter {
                     write!(f, ", {value}")?;
                 }
@@ -59,14 +192,53 @@ impl<K: Display> Display for InvalidLayoutEncoding<K> {
             }
             Ok(())
         }
+        let problems = &self.0;
         writeln!(f, "Invalid layout encoding.")?;
-        if let Some([l1, l2]) = self.different_counts {
-            writeln!(f, "Number of keys ({l1}) and chars ({l2}) are different!")?;
-        }
-        print_duplicates(f, &self.duplicate_keys, "key")?;
-        print_duplicates(f, &self.duplicate_chars, "char")?;
+        print_duplicates(f, &problems.duplicate_keys, "key")?;
+        print_duplicates(f, &problems.duplicate_chars, "char")?;
+        print_list(
+            f,
+            &problems.invalid_base_chars,
+            "Base chars missing from the layout",
+        )?;
+        print_list(
+            f,
+            &problems.invalid_modifier_keys,
+            "Keys not belonging to their modifier",
+        )?;
         Ok(())
     }
 }
 
 impl<K: Display + Debug> std::error::Error for InvalidLayoutEncoding<K> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn unequal_counts() {
+        let blank = LayoutEncoding::new(vec![0, 1, 2], vec!['a', 'b'], &[]).unwrap();
+        let layout = blank.encode([(2, 'a'), (0, 'b')]).unwrap();
+        assert_eq!(layout, [2, 0, 1]);
+        assert_eq!(
+            blank.decode(layout).collect::<Vec<_>>(),
+            [(2, 'a'), (0, 'b')]
+        );
+        assert!(matches!(
+            blank.encode([(2, 'a')]),
+            Err(InvalidPin::MissingChar('b'))
+        ));
+        let dropped = LayoutEncoding::new(vec![0, 1], vec!['a', 'b', 'c'], &[]).unwrap();
+        let layout = dropped.encode([(0, 'c'), (1, 'a')]).unwrap();
+        assert_eq!(layout, [1, 2, 0]);
+        assert_eq!(
+            dropped.decode(layout).collect::<Vec<_>>(),
+            [(1, 'a'), (0, 'c')]
+        );
+        assert!(matches!(
+            dropped.encode([(0, 'c')]),
+            Err(InvalidPin::MissingChar('b'))
+        ));
+    }
+}
@@ -1,10 +1,22 @@
 pub struct Metric<const N: usize> {
     data: Box<[u64]>,
     side: usize,
+    // Keys of the layout, the ones after them are pseudo keys of dropped
+    // chars.
+    keys_len: usize,
+    penalty: u64,
 }
 
 impl<const N: usize> Metric<N> {
-    pub fn new(side: usize, weight: impl Fn([usize; N]) -> u64) -> Self {
+    /// Creates a metric of `side` keys. Ngrams with chars on keys past the
+    /// first `keys_len` ones weigh `penalty`, which defaults to the
+    /// highest weight.
+    pub fn new(
+        side: usize,
+        keys_len: usize,
+        penalty: Option<u64>,
+        weight: impl Fn([usize; N]) -> u64,
+    ) -> Self {
         let total_size = side.checked_pow(N.try_into().unwrap()).unwrap();
         let mut data = Vec::with_capacity(total_size);
         let mut key = [0; N];
@@ -20,13 +32,34 @@ impl<const N: usize> Metric<N> {
             break;
         }
         assert_eq!(total_size, data.len());
+        let penalty = penalty.unwrap_or_else(|| data.iter().copied().max().unwrap_or(0));
         Self {
             data: data.into_boxed_slice(),
             side,
+            keys_len,
+            penalty,
         }
     }
 
     pub fn weight(&self, ngram: &[usize; N], layout: &[usize]) -> u64 {
-        self.data[ngram.iter().fold(0, |acc, i| acc * self.side + layout[*i])]
+        let mut index = 0;
+        for &c in ngram {
+            let key = layout[c];
+            if key >= self.keys_len {
+                return self.penalty;
+            }
+            index = index * self.side + key;
+        }
+        self.data[index]
+    }
+
+    /// Weight of ngrams with dropped chars.
+    pub fn penalty(&self) -> u64 {
+        self.penalty
+    }
+
+    /// Weight of already pressed keys.
+    pub fn key_weight(&self, nstroke: &[usize; N]) -> u64 {
+        self.data[nstroke.iter().fold(0, |acc, k| acc * self.side + k)]
     }
 }
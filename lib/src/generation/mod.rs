@@ -1,12 +1,19 @@
 mod corpus_set;
+mod coverage;
 mod encoding;
 mod generator;
 mod layout_encoding;
 mod metric;
+mod modifier;
+mod report;
 
 use corpus_set::CorpusSet;
+pub use coverage::{CorpusCoverage, UnknownChars};
 use encoding::Encoding;
-pub use generator::Generator;
+pub use generator::{Generator, GeneratorError, GeneratorOptions, InvalidCorpora, InvalidPin};
 pub use layout_encoding::InvalidLayoutEncoding;
 use layout_encoding::LayoutEncoding;
 use metric::Metric;
+pub use modifier::Modifier;
+use modifier::Modifiers;
+pub use report::{CorpusReport, Report, ScoreInterval};
@@ -0,0 +1,176 @@
+use super::Encoding;
+use std::hash::Hash;
+
+/// Key held while pressing another key to type characters which aren't
+/// on the layout, like shift.
+pub struct Modifier<K> {
+    pub(super) keys: Vec<K>,
+    pub(super) chars: Vec<(char, char)>,
+    pub(super) rule: Box<dyn Fn(&K) -> K>,
+}
+
+impl<K> Modifier<K> {
+    /// Creates a modifier which can be pressed with any of `keys`.
+    /// `chars` maps characters typed with the modifier to characters on
+    /// the layout, e.g. `('A', 'a')`. `rule` chooses which of `keys` is
+    /// pressed together with the key of a character, so it can depend on
+    /// the layout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::Modifier;
+    /// // Keys are numbered from left to right, 10 and 11 are shifts.
+    /// let shift = Modifier::new(
+    ///     [10, 11],
+    ///     ('a'..='z').map(|c| (c.to_ascii_uppercase(), c)),
+    ///     |&key: &usize| if key < 5 { 11 } else { 10 },
+    /// );
+    /// ```
+    pub fn new(
+        keys: impl IntoIterator<Item = K>,
+        chars: impl IntoIterator<Item = (char, char)>,
+        rule: impl Fn(&K) -> K + 'static,
+    ) -> Self {
+        Self {
+            keys: keys.into_iter().collect(),
+            chars: chars.into_iter().collect(),
+            rule: Box::new(rule),
+        }
+    }
+}
+
+/// Modifiers resolved for an encoding. Keys and chars of modifiers are
+/// encoded after the ones of the layout.
+#[derive(Clone, Debug, Default)]
+pub struct Modifiers {
+    // Modifier and base char of every modified char.
+    chars: Vec<(usize, usize)>,
+    // Modifier key pressed with every key of the layout, for every
+    // modifier.
+    keys: Vec<Vec<usize>>,
+    keys_len: usize,
+    chars_len: usize,
+}
+
+impl Modifiers {
+    /// Resolves `modifiers` for a layout of `keys_len` keys and
+    /// `chars_len` chars. On failure returns invalid base chars and keys
+    /// chosen by rules which don't belong to their modifier.
+    pub fn new<K: Clone + Eq + Hash>(
+        keys: &Encoding<K>,
+        chars: &Encoding<char>,
+        keys_len: usize,
+        chars_len: usize,
+        modifiers: &[Modifier<K>],
+    ) -> Result<Self, (Vec<char>, Vec<K>)> {
+        let mut invalid_chars = Vec::new();
+        let mut invalid_keys = Vec::new();
+        let mut res = Self {
+            keys_len,
+            chars_len,
+            ..Self::default()
+        };
+        for (m, modifier) in modifiers.iter().enumerate() {
+            for &(_, base) in &modifier.chars {
+                match chars.encode(&base) {
+                    Some(c) if c < chars_len => res.chars.push((m, c)),
+                    _ => invalid_chars.push(base),
+                }
+            }
+            let mut modifier_keys = Vec::with_capacity(keys_len);
+            for k in 0..keys_len {
+                let key = (modifier.rule)(keys.decode(k));
+                match keys.encode(&key) {
+                    Some(k) if modifier.keys.contains(&key) => modifier_keys.push(k),
+                    _ => invalid_keys.push(key),
+                }
+            }
+            res.keys.push(modifier_keys);
+        }
+        match invalid_chars.is_empty() && invalid_keys.is_empty() {
+            true => Ok(res),
+            false => Err((invalid_chars, invalid_keys)),
+        }
+    }
+
+    pub fn is_modified(&self, c: usize) -> bool {
+        c >= self.chars_len
+    }
+
+    /// Char on the layout used to type `c`.
+    pub fn base(&self, c: usize) -> usize {
+        match self.is_modified(c) {
+            true => self.chars[c - self.chars_len].1,
+            false => c,
+        }
+    }
+
+    /// Number of windows of keys scored for `ngram`, which is the number
+    /// of keys pressed to type its first char. It doesn't depend on the
+    /// layout.
+    pub fn windows<const N: usize>(&self, ngram: &[usize; N]) -> u64 {
+        match self.is_modified(ngram[0]) {
+            true => 2,
+            false => 1,
+        }
+    }
+
+    /// Calls `f` with every window of keys pressed to type `ngram` on
+    /// `layout`, which starts in the keys of its first char. Returns
+    /// `false` without calling `f` if any char of `ngram` is dropped.
+    pub fn for_each_window<const N: usize>(
+        &self,
+        ngram: &[usize; N],
+        layout: &[usize],
+        buffer: &mut Vec<usize>,
+        mut f: impl FnMut(&[usize; N]),
+    ) -> bool {
+        buffer.clear();
+        let mut first = 0;
+        for &c in ngram {
+            let key = layout[self.base(c)];
+            if key >= self.keys_len {
+                return false;
+            }
+            match self.is_modified(c) {
+                true => {
+                    let (m, _) = self.chars[c - self.chars_len];
+                    buffer.extend([self.keys[m][key], key]);
+                }
+                false => buffer.push(key),
+            }
+            if first == 0 {
+                first = buffer.len();
+            }
+        }
+        for window in buffer.windows(N).take(first) {
+            f(window.try_into().unwrap());
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn keystrokes() {
+        let keys = Encoding::new(vec!['a', 'b', 'L', 'R']).unwrap();
+        let chars = Encoding::new(vec!['x', 'y', 'X']).unwrap();
+        let shift = Modifier::new(['L', 'R'], [('X', 'x')], |&key| match key {
+            'a' => 'R',
+            _ => 'L',
+        });
+        let modifiers = Modifiers::new(&keys, &chars, 2, 2, &[shift]).unwrap();
+        let mut windows = Vec::new();
+        let mut buffer = Vec::new();
+        // 'x' is on 'b', so it's shifted with the left shift.
+        modifiers.for_each_window(&[2, 1], &[1, 0], &mut buffer, |w| windows.push(*w));
+        modifiers.for_each_window(&[1, 2], &[1, 0], &mut buffer, |w| windows.push(*w));
+        assert_eq!(windows, vec![[2, 1], [1, 0], [0, 2]]);
+        assert_eq!(modifiers.windows(&[2, 1]), 2);
+        assert_eq!(modifiers.windows(&[1, 2]), 1);
+    }
+}
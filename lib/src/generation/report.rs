@@ -0,0 +1,228 @@
+use super::CorpusCoverage;
+use crate::Pattern;
+use std::fmt::{self, Display, Formatter};
+
+/// Analysis of a layout on every corpus used by a generator.
+#[derive(Clone, Debug)]
+pub struct Report {
+    corpora: Vec<CorpusReport>,
+}
+
+/// Analysis of a layout on a single corpus.
+#[derive(Clone, Debug)]
+pub struct CorpusReport {
+    sum: u64,
+    total: f64,
+    patterns: [u64; Pattern::ALL.len()],
+    warning: Option<CorpusCoverage>,
+}
+
+/// Interval of scores of a layout on resampled corpora, see
+/// [`Generator::bootstrap`](crate::Generator::bootstrap).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScoreInterval {
+    /// Mean score on the resamples.
+    pub mean: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl ScoreInterval {
+    // Percentile interval containing `confidence` of `scores`.
+    pub(super) fn new(mut scores: Vec<f64>, confidence: f64) -> Self {
+        scores.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+        let percentile = |p: f64| {
+            let i = ((scores.len() - 1) as f64 * p).round() as usize;
+            scores[i]
+        };
+        let tail = (1.0 - confidence.clamp(0.0, 1.0)) / 2.0;
+        Self {
+            mean: scores.iter().sum::<f64>() / scores.len() as f64,
+            lower: percentile(tail),
+            upper: percentile(1.0 - tail),
+        }
+    }
+
+    /// Checks if intervals overlap. Layouts whose intervals don't overlap
+    /// differ in score regardless of the texts chosen for the corpus.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.lower <= other.upper && other.lower <= self.upper
+    }
+}
+
+impl Report {
+    pub(super) fn new(corpora: Vec<CorpusReport>) -> Self {
+        Self { corpora }
+    }
+
+    /// Reports for corpora in the order they were given to the generator.
+    pub fn corpora(&self) -> &[CorpusReport] {
+        &self.corpora
+    }
+}
+
+impl CorpusReport {
+    pub(super) fn new(
+        sum: u64,
+        total: f64,
+        patterns: [u64; Pattern::ALL.len()],
+        warning: Option<CorpusCoverage>,
+    ) -> Self {
+        Self {
+            sum,
+            total,
+            patterns,
+            warning,
+        }
+    }
+
+    /// Value of the metric averaged over ngrams of the corpus. This is
+    /// the same value generator is minimizing.
+    pub fn score(&self) -> f64 {
+        self.sum as f64 / self.total
+    }
+
+    /// Raw value of the metric, which is the sum of weights of ngrams of
+    /// the corpus multiplied by their counts.
+    pub fn sum(&self) -> u64 {
+        self.sum
+    }
+
+    /// Number of ngrams of the corpus the score is averaged over. Ngrams
+    /// starting with a modified char count once for every window of keys
+    /// scored.
+    pub fn total(&self) -> f64 {
+        self.total
+    }
+
+    /// Coverage of the corpus if it lost ngrams because of unknown chars
+    /// and [`UnknownChars::Warn`](crate::UnknownChars::Warn) is used.
+    pub fn warning(&self) -> Option<&CorpusCoverage> {
+        self.warning.as_ref()
+    }
+
+    /// Percentage of ngrams of the corpus that follow `pattern`.
+    pub fn percentage(&self, pattern: Pattern) -> f64 {
+        self.patterns[pattern as usize] as f64 / self.total * 100.0
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, corpus) in self.corpora.iter().enumerate() {
+            writeln!(f, "Corpus {}: {corpus}", i + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for CorpusReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "score {:.4}", self.score())?;
+        for pattern in Pattern::ALL {
+            write!(f, ", {pattern} {:.2}%", self.percentage(pattern))?;
+        }
+        if let Some(coverage) = &self.warning {
+            write!(f, "\nWarning: {coverage}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Finger, Generator, GeneratorOptions, Modifier, Pattern, PhysicalKey, UnknownChars,
+    };
+
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    struct Key(Finger);
+
+    impl PhysicalKey for Key {
+        fn finger(&self) -> Finger {
+            self.0
+        }
+    }
+
+    #[test]
+    pub fn patterns() {
+        use Finger::*;
+        let corpus = [
+            (['a', 'a'], 1),
+            (['a', 'b'], 2),
+            (['a', 'c'], 3),
+            (['b', 'a'], 4),
+        ];
+        let keys = [LeftIndex, LeftMiddle, RightIndex].map(Key);
+        let metric = |[k1, k2]: [Key; 2]| (k1.0.hand() == k2.0.hand()) as u64;
+        let generator = Generator::new(keys.clone(), ['a', 'b', 'c'], [corpus], metric).unwrap();
+        let layout = keys.into_iter().zip(['a', 'b', 'c']);
+        let report = generator.report(layout).unwrap();
+        let report = &report.corpora()[0];
+        assert_eq!(report.sum(), 7);
+        assert_eq!(report.total(), 10.0);
+        assert_eq!(report.score(), 0.7);
+        let percentages = Pattern::ALL.map(|pattern| report.percentage(pattern));
+        assert_eq!(percentages, [10.0, 0.0, 30.0, 60.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    pub fn modified_patterns() {
+        use Finger::*;
+        let shift = Key(LeftPinky);
+        let modifier = Modifier::new([shift.clone()], [('A', 'a'), ('B', 'b')], move |_| {
+            shift.clone()
+        });
+        let options = GeneratorOptions {
+            modifiers: vec![modifier],
+            ..GeneratorOptions::default()
+        };
+        let keys = [Key(LeftIndex), Key(RightIndex)];
+        let metric = |[k1, k2]: [Key; 2]| (k1.0.hand() == k2.0.hand()) as u64;
+        let corpus = [(['A', 'b'], 1), (['a', 'b'], 1)];
+        let generator =
+            Generator::with_options(keys.clone(), ['a', 'b'], [corpus], metric, options).unwrap();
+        let layout = keys.into_iter().zip(['a', 'b']);
+        let report = generator.report(layout).unwrap();
+        let report = &report.corpora()[0];
+        // Shift and 'a' make the only one-handed window out of 3.
+        assert_eq!((report.sum(), report.total()), (1, 3.0));
+        let percentages = Pattern::ALL.map(|pattern| report.percentage(pattern));
+        assert!((percentages.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+        assert!((report.percentage(Pattern::Roll) - 100.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    pub fn unknown_chars_warning() {
+        use Finger::*;
+        let keys = [Key(LeftIndex), Key(RightIndex)];
+        let metric = |[k1, k2]: [Key; 2]| (k1.0.hand() == k2.0.hand()) as u64;
+        let corpora = [
+            vec![(['a', 'b'], 3), (['b', 'é'], 1)],
+            vec![(['a', 'b'], 1)],
+        ];
+        let report = |unknown_chars| {
+            let options = GeneratorOptions {
+                unknown_chars,
+                ..GeneratorOptions::default()
+            };
+            let generator =
+                Generator::with_options(keys.clone(), ['a', 'b'], corpora.clone(), metric, options);
+            let layout = keys.clone().into_iter().zip(['a', 'b']);
+            generator.unwrap().report(layout).unwrap()
+        };
+        let warned = report(UnknownChars::Warn);
+        let warning = warned.corpora()[0].warning().unwrap();
+        assert_eq!((warning.dropped, warning.total), (1, 4));
+        // Corpora which didn't lose anything aren't warned about.
+        assert_eq!(warned.corpora()[1].warning(), None);
+        assert!(warned
+            .to_string()
+            .contains("Warning: dropped 25.00% of ngrams"));
+        let dropped = report(UnknownChars::Drop);
+        assert!(dropped
+            .corpora()
+            .iter()
+            .all(|corpus| corpus.warning().is_none()));
+    }
+}
@@ -1,7 +1,19 @@
 mod expansion;
 mod generation;
+mod metrics;
 mod ngram_data;
 
-pub use expansion::{expand_first, expand_full};
-pub use generation::Generator;
-pub use ngram_data::NgramData;
+pub use expansion::{
+    expand_document, expand_end, expand_first, expand_full, expand_inner, expand_start, Context,
+};
+pub use generation::{
+    CorpusCoverage, CorpusReport, Generator, GeneratorError, GeneratorOptions, InvalidCorpora,
+    Modifier, Report, ScoreInterval, UnknownChars,
+};
+pub use metrics::{fingerings, Effort, Finger, Hand, KeyPosition, Pattern, PhysicalKey, Travel};
+pub use ngram_data::{
+    checksum, AlphabetTooLarge, Block, Blocks, Boundary, CodeOptions, CompactNgramData, Coverage,
+    FileInfo, FormatError, IngestError, InvalidBytes, IoError, KeyAction, KeyEvent, KeyLog,
+    KeyLogError, Language, LogFormat, NgramData, NgramIndex, Normalization, Pruned, SourceFiles,
+    SourceInfo, TableError, TableFormat, Timing, Timings, UnicodeForm,
+};
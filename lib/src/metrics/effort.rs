@@ -0,0 +1,122 @@
+use super::{Finger, PhysicalKey};
+
+/// Monogram metric made of per-key effort and per-finger strength.
+///
+/// Cost of a key is its effort multiplied by the multiplier of the
+/// finger pressing it. If the key can be pressed by multiple fingers
+/// the cheapest one is used. All multipliers start at `1.0`.
+///
+/// # Example
+///
+/// ```
+/// use algae_lib::{Effort, Finger, Generator, NgramData, PhysicalKey};
+///
+/// #[derive(Clone, PartialEq, Eq, Hash)]
+/// struct Key(usize);
+///
+/// impl PhysicalKey for Key {
+///     fn finger(&self) -> Finger {
+///         Finger::ALL[self.0]
+///     }
+/// }
+///
+/// // Left pinky hurts, so keys pressed by it should be avoided.
+/// let effort = Effort::new(|_: &Key| 10).penalize(Finger::LeftPinky, 3.0);
+/// assert_eq!(effort.cost([Key(0)]), 30);
+/// assert_eq!(effort.cost([Key(1)]), 10);
+///
+/// let corpus = NgramData::<1>::new("abba".chars());
+/// let generator = Generator::new(
+///     [Key(0), Key(1)],
+///     ['a', 'b'],
+///     [corpus.ngrams::<1>()],
+///     |keys| effort.cost(keys),
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Effort<E> {
+    effort: E,
+    multipliers: [f64; 10],
+}
+
+impl<E> Effort<E> {
+    /// Creates the metric from a function returning effort of a key.
+    pub fn new(effort: E) -> Self {
+        Self {
+            effort,
+            multipliers: [1.0; 10],
+        }
+    }
+
+    /// Replaces strength multipliers of all fingers. Multipliers are
+    /// ordered the same way as [`Finger::ALL`].
+    pub fn with_multipliers(mut self, multipliers: [f64; 10]) -> Self {
+        self.multipliers = multipliers;
+        self
+    }
+
+    /// Multiplies cost of every key pressed by `finger` by `factor`.
+    /// Meant for injured or weak fingers.
+    pub fn penalize(mut self, finger: Finger, factor: f64) -> Self {
+        self.multipliers[finger as usize] *= factor;
+        self
+    }
+
+    pub fn cost<K>(&self, [key]: [K; 1]) -> u64
+    where
+        K: PhysicalKey,
+        E: Fn(&K) -> u64,
+    {
+        let effort = (self.effort)(&key) as f64;
+        key.fingers()
+            .into_iter()
+            .map(|finger| (effort * self.multipliers[finger as usize]).round() as u64)
+            .min()
+            .expect("key must be pressable by some finger")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Generator;
+
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    struct Key(Vec<Finger>);
+
+    impl PhysicalKey for Key {
+        fn finger(&self) -> Finger {
+            self.0[0]
+        }
+
+        fn fingers(&self) -> Vec<Finger> {
+            self.0.clone()
+        }
+    }
+
+    #[test]
+    pub fn penalized_fingers() {
+        use Finger::*;
+        let effort = Effort::new(|key: &Key| 10 * key.0.len() as u64)
+            .penalize(LeftPinky, 3.0)
+            .penalize(LeftPinky, 2.0)
+            .penalize(LeftRing, 0.5);
+        assert_eq!(effort.cost([Key(vec![LeftPinky])]), 60);
+        assert_eq!(effort.cost([Key(vec![LeftRing])]), 5);
+        assert_eq!(effort.cost([Key(vec![LeftMiddle])]), 10);
+        // The cheapest finger is used.
+        assert_eq!(effort.cost([Key(vec![LeftPinky, LeftRing])]), 10);
+
+        let keys = [LeftPinky, LeftRing, LeftMiddle].map(|finger| Key(vec![finger]));
+        let corpus = [(['a'], 1), (['b'], 5), (['c'], 3)];
+        let generator = Generator::new(keys.clone(), ['a', 'b', 'c'], [corpus], |keys| {
+            effort.cost(keys)
+        })
+        .unwrap();
+        let layout: Vec<_> = generator.generate([]).unwrap();
+        let expected = [(&keys[0], 'a'), (&keys[1], 'b'), (&keys[2], 'c')];
+        for (key, char) in expected {
+            assert!(layout.contains(&(key.clone(), char)));
+        }
+    }
+}
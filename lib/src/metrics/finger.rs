@@ -0,0 +1,127 @@
+/// Finger used to press a key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Finger {
+    LeftPinky,
+    LeftRing,
+    LeftMiddle,
+    LeftIndex,
+    LeftThumb,
+    RightThumb,
+    RightIndex,
+    RightMiddle,
+    RightRing,
+    RightPinky,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+impl Finger {
+    /// All fingers ordered from left pinky to right pinky.
+    pub const ALL: [Finger; 10] = [
+        Finger::LeftPinky,
+        Finger::LeftRing,
+        Finger::LeftMiddle,
+        Finger::LeftIndex,
+        Finger::LeftThumb,
+        Finger::RightThumb,
+        Finger::RightIndex,
+        Finger::RightMiddle,
+        Finger::RightRing,
+        Finger::RightPinky,
+    ];
+
+    pub fn hand(self) -> Hand {
+        match self as usize {
+            0..=4 => Hand::Left,
+            _ => Hand::Right,
+        }
+    }
+}
+
+/// Physical description of a key used by built-in metrics.
+pub trait PhysicalKey {
+    /// Finger that presses the key.
+    fn finger(&self) -> Finger;
+
+    /// Every finger that can press the key, preferred one first. Must
+    /// contain at least one finger. Built-in metrics use the fingering
+    /// with the lowest cost for the whole ngram. Since metrics get
+    /// precomputed for every ngram of keys, the choice doesn't slow down
+    /// generation.
+    fn fingers(&self) -> Vec<Finger> {
+        vec![self.finger()]
+    }
+}
+
+/// Position of a key, needed by metrics measuring distances like
+/// [`Travel`](crate::Travel).
+pub trait KeyPosition: PhysicalKey {
+    /// Position of the center of the key as `[x, y]`. Unit is up to the
+    /// user, but it should be the same for every key.
+    fn position(&self) -> [f64; 2];
+}
+
+/// Lists every way `keys` can be typed given alternate fingerings of
+/// keys. Fingerings of earlier keys change the slowest.
+///
+/// # Example
+///
+/// ```
+/// use algae_lib::{fingerings, Finger, PhysicalKey};
+///
+/// struct Key(Vec<Finger>);
+///
+/// impl PhysicalKey for Key {
+///     fn finger(&self) -> Finger {
+///         self.0[0]
+///     }
+///
+///     fn fingers(&self) -> Vec<Finger> {
+///         self.0.clone()
+///     }
+/// }
+///
+/// use Finger::*;
+/// let b = Key(vec![LeftIndex, RightIndex]);
+/// let t = Key(vec![LeftIndex]);
+/// assert_eq!(
+///     fingerings(&[b, t]),
+///     vec![vec![LeftIndex, LeftIndex], vec![RightIndex, LeftIndex]],
+/// );
+/// ```
+pub fn fingerings<K: PhysicalKey>(keys: &[K]) -> Vec<Vec<Finger>> {
+    keys.iter().fold(vec![Vec::new()], |fingerings, key| {
+        let fingers = key.fingers();
+        assert!(!fingers.is_empty(), "key must be pressable by some finger");
+        fingerings
+            .into_iter()
+            .flat_map(|fingering| {
+                fingers.iter().map(move |&finger| {
+                    let mut fingering = fingering.clone();
+                    fingering.push(finger);
+                    fingering
+                })
+            })
+            .collect()
+    })
+}
+
+impl<K: PhysicalKey + ?Sized> PhysicalKey for &K {
+    fn finger(&self) -> Finger {
+        (**self).finger()
+    }
+
+    fn fingers(&self) -> Vec<Finger> {
+        (**self).fingers()
+    }
+}
+
+impl<K: KeyPosition + ?Sized> KeyPosition for &K {
+    fn position(&self) -> [f64; 2] {
+        (**self).position()
+    }
+}
@@ -0,0 +1,9 @@
+mod effort;
+mod finger;
+mod pattern;
+mod travel;
+
+pub use effort::Effort;
+pub use finger::{fingerings, Finger, Hand, KeyPosition, PhysicalKey};
+pub use pattern::Pattern;
+pub use travel::Travel;
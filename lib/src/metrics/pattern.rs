@@ -0,0 +1,108 @@
+use super::{fingerings, Finger, PhysicalKey};
+use std::fmt::{self, Display, Formatter};
+
+/// Kind of movement needed to type an ngram.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Pattern {
+    /// The same key is pressed twice in a row.
+    SameKey,
+    /// The same finger presses two different keys in a row.
+    SameFinger,
+    /// Every press is done by a different hand than the previous one.
+    Alternation,
+    /// Some consecutive presses are done by the same hand, some not.
+    /// Bigrams typed by a single hand are also considered rolls.
+    Roll,
+    /// Every press is done by the same hand and the direction of
+    /// movement changes.
+    Redirect,
+    /// Every press is done by the same hand in a single direction.
+    OneHand,
+}
+
+impl Pattern {
+    pub const ALL: [Pattern; 6] = [
+        Pattern::SameKey,
+        Pattern::SameFinger,
+        Pattern::Alternation,
+        Pattern::Roll,
+        Pattern::Redirect,
+        Pattern::OneHand,
+    ];
+
+    /// Classifies a sequence of keys. Returns `None` for sequences
+    /// shorter than 2. When keys can be pressed by multiple fingers, the
+    /// first fingering that avoids using the same finger twice in a row
+    /// is used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{Finger, Pattern, PhysicalKey};
+    ///
+    /// #[derive(PartialEq)]
+    /// struct Key(Finger);
+    ///
+    /// impl PhysicalKey for Key {
+    ///     fn finger(&self) -> Finger {
+    ///         self.0
+    ///     }
+    /// }
+    ///
+    /// use Finger::*;
+    /// let redirect = [Key(LeftIndex), Key(LeftPinky), Key(LeftMiddle)];
+    /// assert_eq!(Pattern::of(&redirect), Some(Pattern::Redirect));
+    /// let roll = [Key(LeftIndex), Key(LeftMiddle), Key(RightIndex)];
+    /// assert_eq!(Pattern::of(&roll), Some(Pattern::Roll));
+    /// ```
+    pub fn of<K>(keys: &[K]) -> Option<Self>
+    where
+        K: PhysicalKey + PartialEq,
+    {
+        if keys.len() < 2 {
+            return None;
+        }
+        if keys.windows(2).any(|w| w[0] == w[1]) {
+            return Some(Pattern::SameKey);
+        }
+        fingerings(keys)
+            .iter()
+            .map(|fingers| Self::of_fingers(fingers))
+            .find(|pattern| *pattern != Pattern::SameFinger)
+            .or(Some(Pattern::SameFinger))
+    }
+
+    fn of_fingers(fingers: &[Finger]) -> Self {
+        if fingers.windows(2).any(|w| w[0] == w[1]) {
+            return Pattern::SameFinger;
+        }
+        let same_hand = fingers.windows(2).map(|w| w[0].hand() == w[1].hand());
+        if same_hand.clone().all(|same| !same) {
+            return Pattern::Alternation;
+        }
+        if !same_hand.clone().all(|same| same) || fingers.len() == 2 {
+            return Pattern::Roll;
+        }
+        let mut directions = fingers.windows(2).map(|w| w[0] < w[1]);
+        let first = directions.next();
+        match directions.all(|direction| Some(direction) == first) {
+            true => Pattern::OneHand,
+            false => Pattern::Redirect,
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use Pattern::*;
+        let name = match self {
+            SameKey => "same key",
+            SameFinger => "same finger",
+            Alternation => "alternation",
+            Roll => "roll",
+            Redirect => "redirect",
+            OneHand => "one hand",
+        };
+        write!(f, "{name}")
+    }
+}
@@ -0,0 +1,135 @@
+use super::{fingerings, Finger, KeyPosition};
+
+/// Finger travel metric which keeps track of where each finger rests.
+///
+/// All fingers start at their home positions. Pressing a key moves the
+/// finger onto it and the finger stays there until its next press. Only
+/// the distance travelled during the last press of the ngram is counted,
+/// earlier presses serve as context. This way every keystroke of the
+/// corpus is counted once while still knowing that a finger can be
+/// displaced by a press that happened a few keys earlier, so it's best
+/// used with trigrams or longer ngrams. When keys can be pressed by
+/// multiple fingers, the fingering with the shortest travel is used.
+///
+/// Distances are euclidean, measured in units of
+/// [`KeyPosition::position`] and multiplied by scale (`100` by default)
+/// before being rounded.
+///
+/// # Example
+///
+/// ```
+/// use algae_lib::{Finger, KeyPosition, PhysicalKey, Travel};
+///
+/// struct Key(Finger, [f64; 2]);
+///
+/// impl PhysicalKey for Key {
+///     fn finger(&self) -> Finger {
+///         self.0
+///     }
+/// }
+///
+/// impl KeyPosition for Key {
+///     fn position(&self) -> [f64; 2] {
+///         self.1
+///     }
+/// }
+///
+/// let travel = Travel::new(|finger| [finger as usize as f64, 1.0]);
+/// let top = Key(Finger::LeftPinky, [0.0, 0.0]);
+/// let home = Key(Finger::LeftPinky, [0.0, 1.0]);
+/// let other = Key(Finger::LeftRing, [1.0, 1.0]);
+/// // Pinky moved to the top row earlier and has to go back home.
+/// assert_eq!(travel.cost([top, other, home]), 100);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Travel {
+    homes: [[f64; 2]; 10],
+    scale: f64,
+}
+
+impl Travel {
+    /// Creates the metric from home positions of fingers.
+    pub fn new(home: impl Fn(Finger) -> [f64; 2]) -> Self {
+        Self {
+            homes: Finger::ALL.map(home),
+            scale: 100.0,
+        }
+    }
+
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn cost<K, const N: usize>(&self, keys: [K; N]) -> u64
+    where
+        K: KeyPosition,
+    {
+        fingerings(&keys)
+            .into_iter()
+            .map(|fingers| self.travel(&keys, &fingers))
+            .min()
+            .unwrap()
+    }
+
+    fn travel<K: KeyPosition>(&self, keys: &[K], fingers: &[Finger]) -> u64 {
+        let mut positions = self.homes;
+        let mut distance = 0.0;
+        for (key, &finger) in keys.iter().zip(fingers) {
+            let [x1, y1] = positions[finger as usize];
+            let [x2, y2] = key.position();
+            distance = (x2 - x1).hypot(y2 - y1);
+            positions[finger as usize] = [x2, y2];
+        }
+        (distance * self.scale).round() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PhysicalKey;
+    use Finger::*;
+
+    struct Key(Vec<Finger>, [f64; 2]);
+
+    impl PhysicalKey for Key {
+        fn finger(&self) -> Finger {
+            self.0[0]
+        }
+
+        fn fingers(&self) -> Vec<Finger> {
+            self.0.clone()
+        }
+    }
+
+    impl KeyPosition for Key {
+        fn position(&self) -> [f64; 2] {
+            self.1
+        }
+    }
+
+    #[test]
+    pub fn travel() {
+        let travel = Travel::new(|finger| [finger as usize as f64, 0.0]);
+        let home = Key(vec![LeftRing], [1.0, 0.0]);
+        let up = Key(vec![LeftRing], [1.0, -1.0]);
+        let diagonal = Key(vec![LeftRing], [4.0, 4.0]);
+        let other = Key(vec![RightPinky], [9.0, 1.0]);
+        assert_eq!(travel.cost([&home]), 0);
+        assert_eq!(travel.cost([&diagonal]), 500);
+        // Only the last press is counted.
+        assert_eq!(travel.cost([&up, &home]), 100);
+        assert_eq!(travel.cost([&up, &other]), 100);
+        assert_eq!(travel.cost([&up, &up]), 0);
+        // Fingers stay where they pressed last.
+        assert_eq!(travel.cost([&diagonal, &other, &up]), 583);
+        assert_eq!(travel.clone().with_scale(10.0).cost([&up, &diagonal]), 58);
+
+        // The fingering with the shortest travel is used.
+        let shared = Key(vec![LeftIndex, LeftMiddle], [2.8, 0.0]);
+        assert_eq!(travel.cost([&shared]), 20);
+        let index = Key(vec![LeftIndex], [3.0, -3.0]);
+        assert_eq!(travel.cost([&index, &shared]), 80);
+    }
+}
@@ -0,0 +1,281 @@
+use super::NgramData;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+
+impl<const N: usize> NgramData<N> {
+    /// Creates `NgramData` with every count multiplied by `factor` and
+    /// rounded. Ngrams whose count gets rounded to 0 are removed.
+    ///
+    /// Counts of ngrams, tails and heads are rounded independently, so
+    /// unless `factor` is a whole number the result doesn't correspond
+    /// to any text. Shorter ngrams and expansions of it are then only
+    /// approximate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let corpus = NgramData::<2>::new("aaab".chars());
+    /// let scaled = corpus.scale(2.0);
+    /// assert_eq!(scaled.ngrams::<2>().get(&['a', 'a']), Some(&4));
+    /// ```
+    pub fn scale(&self, factor: f64) -> Self {
+        fn scale<T: Clone + Eq + Hash>(counts: &HashMap<T, u64>, factor: f64) -> HashMap<T, u64> {
+            counts
+                .iter()
+                .map(|(ngram, &count)| (ngram.clone(), (count as f64 * factor).round() as u64))
+                .filter(|(_, count)| *count > 0)
+                .collect()
+        }
+        Self {
+            ngrams: scale(&self.ngrams, factor),
+            tails: scale(&self.tails, factor),
+            heads: scale(&self.heads, factor),
+            short: scale(&self.short, factor),
+            normalization: self.normalization,
+            boundary: self.boundary.clone(),
+            contraction_only: self.contraction_only,
+        }
+    }
+
+    /// Creates a single corpus out of weighted corpora. Every corpus is
+    /// scaled so that its share of characters in the result is
+    /// proportional to its weight. The result has about as many
+    /// characters as all corpora combined. Like with
+    /// [`NgramData::scale`], its shorter ngrams and expansions are
+    /// approximate.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if corpora use different normalization
+    /// or boundary, any weight is negative or not finite, or weights of non-empty
+    /// corpora sum up to 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let prose = NgramData::<2>::new("aaaaaaaaa".chars());
+    /// let code = NgramData::<2>::new("{}".chars());
+    /// let blend = NgramData::blend([(&prose, 0.7), (&code, 0.3)]);
+    /// let chars = blend.ngrams::<1>();
+    /// assert_eq!(chars.get(&['a']), Some(&8));
+    /// assert_eq!(chars.get(&['{']), Some(&2));
+    /// ```
+    pub fn blend<'a>(corpora: impl IntoIterator<Item = (&'a Self, f64)>) -> Self {
+        let corpora: Vec<_> = corpora
+            .into_iter()
+            .inspect(|&(_, weight)| {
+                assert!(
+                    weight.is_finite() && weight >= 0.0,
+                    "weights must be finite and non-negative"
+                )
+            })
+            .map(|(corpus, weight)| (corpus, weight, corpus.char_count()))
+            .filter(|(_, _, chars)| *chars > 0)
+            .collect();
+        let weights: f64 = corpora.iter().map(|(_, weight, _)| weight).sum();
+        assert!(
+            corpora.is_empty() || weights > 0.0,
+            "weights of non-empty corpora must sum up to more than 0"
+        );
+        let chars: u64 = corpora.iter().map(|(_, _, chars)| chars).sum();
+        let mut res = match corpora.first() {
+            Some((corpus, _, _)) => corpus.empty_like(),
+            None => Self::empty(),
+        };
+        for (corpus, weight, count) in corpora {
+            let factor = weight / weights * chars as f64 / count as f64;
+            res += corpus.scale(factor);
+        }
+        res
+    }
+
+    // Number of characters in all counted texts.
+    pub(super) fn char_count(&self) -> u64 {
+        let ngrams: u64 = self.ngrams.values().sum();
+        let tails: u64 = self.tails.values().sum();
+        let short: u64 = self
+            .short
+            .iter()
+            .map(|(short, count)| short.len() as u64 * count)
+            .sum();
+        ngrams + tails * N as u64 + short
+    }
+}
+
+/// Merges texts counted in both, see [`NgramData::merge`].
+///
+/// # Panics
+///
+/// This function will panic if `self` and `rhs` use different
+/// normalization or boundary.
+impl<const N: usize> Add for NgramData<N> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        self += &rhs;
+        self
+    }
+}
+
+/// Merges texts counted in both, see [`NgramData::merge`].
+///
+/// # Panics
+///
+/// This function will panic if `self` and `rhs` use different
+/// normalization or boundary.
+impl<const N: usize> AddAssign for NgramData<N> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self += &rhs;
+    }
+}
+
+/// Merges texts counted in both, see [`NgramData::merge`].
+///
+/// # Panics
+///
+/// This function will panic if `self` and `rhs` use different
+/// normalization or boundary.
+impl<const N: usize> AddAssign<&Self> for NgramData<N> {
+    fn add_assign(&mut self, rhs: &Self) {
+        self.merge(rhs);
+    }
+}
+
+/// Removes texts counted in `rhs`. Counts can't go below 0. Result is
+/// exact only if texts of `rhs` were counted as separate documents of
+/// `self`.
+///
+/// # Panics
+///
+/// This function will panic if `self` and `rhs` use different
+/// normalization or boundary.
+impl<const N: usize> Sub for NgramData<N> {
+    type Output = Self;
+
+    fn sub(mut self, rhs: Self) -> Self {
+        self -= &rhs;
+        self
+    }
+}
+
+/// Removes texts counted in `rhs`, see [`Sub`].
+///
+/// # Panics
+///
+/// This function will panic if `self` and `rhs` use different
+/// normalization or boundary.
+impl<const N: usize> SubAssign for NgramData<N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self -= &rhs;
+    }
+}
+
+/// Removes texts counted in `rhs`, see [`Sub`].
+///
+/// # Panics
+///
+/// This function will panic if `self` and `rhs` use different
+/// normalization or boundary.
+impl<const N: usize> SubAssign<&Self> for NgramData<N> {
+    fn sub_assign(&mut self, rhs: &Self) {
+        assert_eq!(
+            self.normalization, rhs.normalization,
+            "tried to subtract ngrams with different normalization"
+        );
+        assert_eq!(
+            self.boundary, rhs.boundary,
+            "tried to subtract ngrams with different boundary"
+        );
+        fn subtract<T: Eq + Hash>(counts: &mut HashMap<T, u64>, other: &HashMap<T, u64>) {
+            for (ngram, count) in other {
+                if let Some(value) = counts.get_mut(ngram) {
+                    *value = value.saturating_sub(*count);
+                    if *value == 0 {
+                        counts.remove(ngram);
+                    }
+                }
+            }
+        }
+        subtract(&mut self.ngrams, &rhs.ngrams);
+        subtract(&mut self.tails, &rhs.tails);
+        subtract(&mut self.heads, &rhs.heads);
+        subtract(&mut self.short, &rhs.short);
+        self.contraction_only |= rhs.contraction_only;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Boundary, Normalization, UnicodeForm};
+
+    #[test]
+    pub fn add_and_subtract() {
+        let documents = ["my typing", "boilerplate", "ok"];
+        let all = NgramData::<3>::from_documents(documents.map(str::chars));
+        let boilerplate = NgramData::new("boilerplate".chars());
+        let mine = NgramData::from_documents(["my typing", "ok"].map(str::chars));
+        assert_eq!(all.clone() - boilerplate.clone(), mine);
+        assert_eq!(mine + boilerplate, all);
+    }
+
+    #[test]
+    pub fn blend_weights() {
+        let prose = NgramData::<2>::new("aaaa".chars());
+        let code = NgramData::<2>::new("{}".chars());
+        let empty = NgramData::<2>::empty();
+        let blend = NgramData::blend([(&prose, 1.0), (&code, 0.0), (&empty, 0.0)]);
+        assert_eq!(blend, prose.scale(1.5));
+        assert!(NgramData::blend([(&empty, 0.0)]).is_empty());
+        for weights in [[0.0, 0.0], [-1.0, 2.0], [f64::NAN, 1.0]] {
+            let blend = std::panic::catch_unwind(|| {
+                NgramData::blend([(&prose, weights[0]), (&code, weights[1])])
+            });
+            assert!(blend.is_err());
+        }
+    }
+
+    #[test]
+    pub fn scale_rounding() {
+        let corpus = NgramData::<2>::new("aaab".chars());
+        let doubled = NgramData::from_documents(["aaab", "aaab"].map(str::chars));
+        assert_eq!(corpus.scale(2.0), doubled);
+        // Counts of 2 and 1 both round to 1, so the ratio isn't kept.
+        let halved = corpus.scale(0.5);
+        assert_eq!(halved.ngrams::<2>().get(&['a', 'a']), Some(&1));
+        assert_eq!(halved.ngrams::<2>().get(&['a', 'b']), Some(&1));
+        let tripled = NgramData::<2>::new("ab".chars()).scale(3.0);
+        assert_eq!(tripled.scale(0.5).ngrams::<2>().get(&['a', 'b']), Some(&2));
+    }
+
+    #[test]
+    pub fn subtract_settings() {
+        let nfc = Normalization {
+            form: Some(UnicodeForm::Nfc),
+            ..Normalization::default()
+        };
+        let composed = NgramData::<2>::empty().with_normalization(nfc);
+        let lines = NgramData::<2>::empty().with_boundary(Boundary::Newline);
+        let empty = NgramData::<2>::empty();
+        assert!(std::panic::catch_unwind(|| composed - empty.clone()).is_err());
+        assert!(std::panic::catch_unwind(|| lines - empty).is_err());
+    }
+
+    #[test]
+    pub fn merge_boundaries() {
+        let lines = NgramData::<2>::empty().with_boundary(Boundary::Newline);
+        let text = NgramData::<2>::new("a\nb".chars());
+        assert!(std::panic::catch_unwind(|| lines.clone() + text.clone()).is_err());
+        assert!(
+            std::panic::catch_unwind(|| NgramData::blend([(&lines, 1.0), (&text, 1.0)])).is_ok()
+        );
+        let mut lines = lines;
+        NgramData::add(&mut lines, "a\nb".chars());
+        assert!(
+            std::panic::catch_unwind(|| NgramData::blend([(&lines, 1.0), (&text, 1.0)])).is_err()
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use super::boundary::{Segments, Token};
+use super::{Boundary, NgramData};
+
+/// Unit of text resampled by the bootstrap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Block {
+    /// Whole documents.
+    Document,
+    /// Chunks of documents with given number of characters. Chunks of a
+    /// document keep ngrams crossing them, so resampling whole documents
+    /// split into chunks gives back the document.
+    Chunk(usize),
+}
+
+/// Corpus split into blocks, which can be drawn with replacement to get
+/// resampled corpora of the same size. Differences between scores of a
+/// layout on the resamples show how much the score depends on the
+/// particular texts chosen for the corpus.
+#[derive(Clone, Debug)]
+pub struct Blocks<const N: usize> {
+    blocks: Vec<NgramData<N>>,
+}
+
+impl<const N: usize> NgramData<N> {
+    /// Splits `documents` into blocks counted with normalization and
+    /// boundary of `self`. Ngrams already counted in `self` are ignored.
+    /// Empty documents are skipped.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `block` is a chunk of 0 characters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{Block, NgramData};
+    /// let documents = ["Quick brown fox", "jumps over", "a lazy dog"];
+    /// let blocks = NgramData::<3>::empty().blocks(documents.map(str::chars), Block::Document);
+    /// assert_eq!(blocks.len(), 3);
+    /// // Resamples depend only on the seed.
+    /// let resample = blocks.resamples(42).next().unwrap();
+    /// assert_eq!(blocks.resamples(42).next(), Some(resample));
+    /// ```
+    pub fn blocks<I>(&self, documents: impl IntoIterator<Item = I>, block: Block) -> Blocks<N>
+    where
+        I: IntoIterator<Item = char>,
+    {
+        let mut blocks = Vec::new();
+        match block {
+            Block::Document => {
+                for document in documents {
+                    let mut res = self.empty_like();
+                    res.add(document);
+                    if !res.is_empty() {
+                        blocks.push(res);
+                    }
+                }
+            }
+            Block::Chunk(len) => {
+                assert!(len > 0, "chunks must contain at least one character");
+                // Documents are normalized and split at boundaries first,
+                // so chunks can be counted without them.
+                let mut empty = self.empty_like();
+                empty.boundary = Boundary::None;
+                for document in documents {
+                    let text = self.normalization.apply(document.into_iter());
+                    let mut texts = vec![Vec::new()];
+                    for token in Segments::new(text, &self.boundary) {
+                        match token {
+                            Token::Char(char, _) => texts.last_mut().unwrap().push(char),
+                            Token::Break => texts.push(Vec::new()),
+                        }
+                    }
+                    for text in texts {
+                        add_chunks(&mut blocks, &empty, &text, len);
+                    }
+                }
+                for block in &mut blocks {
+                    block.boundary = self.boundary.clone();
+                }
+            }
+        }
+        Blocks { blocks }
+    }
+}
+
+// Splits text into chunks owning windows starting in them. Like in
+// parallel counting, only the first chunk keeps its head and only the
+// last one keeps its tail.
+fn add_chunks<const N: usize>(
+    blocks: &mut Vec<NgramData<N>>,
+    empty: &NgramData<N>,
+    text: &[char],
+    len: usize,
+) {
+    if text.len() < N {
+        if !text.is_empty() {
+            let mut res = empty.clone();
+            res.count(text.iter().copied());
+            blocks.push(res);
+        }
+        return;
+    }
+    for start in (0..=text.len() - N).step_by(len) {
+        let end = (start + len + N - 1).min(text.len());
+        let mut res = empty.clone();
+        res.count(text[start..end].iter().copied());
+        if start != 0 {
+            res.heads.clear();
+        }
+        if end != text.len() {
+            for (tail, count) in std::mem::take(&mut res.tails) {
+                *res.ngrams.entry(tail).or_insert(0) += count;
+            }
+        }
+        blocks.push(res);
+    }
+}
+
+impl<const N: usize> Blocks<N> {
+    /// Number of blocks.
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Merges every block back into a single corpus.
+    pub fn corpus(&self) -> NgramData<N> {
+        self.merged(self.blocks.iter())
+    }
+
+    /// Returns an endless iterator of resampled corpora. Each of them
+    /// merges as many blocks as there are in `self`, drawn with
+    /// replacement. Resamples are the same for the same `seed`.
+    pub fn resamples(&self, seed: u64) -> impl Iterator<Item = NgramData<N>> + '_ {
+        let rng = fastrand::Rng::with_seed(seed);
+        std::iter::repeat_with(move || {
+            let len = self.blocks.len();
+            self.merged((0..len).map(|_| &self.blocks[rng.usize(..len)]))
+        })
+    }
+
+    fn merged<'a>(&self, blocks: impl Iterator<Item = &'a NgramData<N>>) -> NgramData<N> {
+        let mut res = match self.blocks.first() {
+            Some(block) => block.empty_like(),
+            None => NgramData::empty(),
+        };
+        for block in blocks {
+            res.merge(block);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Normalization;
+
+    #[test]
+    pub fn chunks() {
+        let documents = ["Quick brown fox", "ab", "jumps. Over a lazy dog"];
+        let normalization = Normalization {
+            lowercase: true,
+            ..Normalization::default()
+        };
+        let template = NgramData::<3>::empty()
+            .with_normalization(normalization)
+            .with_boundary(Boundary::Separator(". ".into()));
+        let mut corpus = template.clone();
+        corpus.add_documents(documents.map(str::chars));
+        for len in [1, 2, 5, 100] {
+            let blocks = template.blocks(documents.map(str::chars), Block::Chunk(len));
+            assert_eq!(blocks.corpus(), corpus);
+        }
+        let blocks = template.blocks(documents.map(str::chars), Block::Document);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks.corpus(), corpus);
+        let resamples: Vec<_> = blocks.resamples(1).take(3).collect();
+        assert_eq!(resamples, blocks.resamples(1).take(3).collect::<Vec<_>>());
+        for resample in resamples {
+            assert_eq!(resample.normalization(), &normalization);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+
+/// Where text should be split into separate documents. Ngrams are never
+/// counted across a boundary and the boundary itself is removed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Boundary {
+    /// Count the whole text as a single document.
+    #[default]
+    None,
+    /// Split text at every newline.
+    Newline,
+    /// Split text at two or more consecutive newlines. Single newlines
+    /// are counted as characters.
+    BlankLine,
+    /// Split text at every occurrence of given string. Empty string
+    /// doesn't split anything.
+    Separator(String),
+}
+
+pub(super) enum Token<T = ()> {
+    Char(char, T),
+    Break,
+}
+
+type Untagged<I> = std::iter::Map<I, fn(char) -> (char, ())>;
+
+// Splits chars tagged with values into documents separated by
+// `Token::Break`.
+pub(super) struct Segments<I, T = ()> {
+    chars: I,
+    boundary: Boundary,
+    separator: Vec<char>,
+    pending: VecDeque<(char, T)>,
+}
+
+impl<I: Iterator<Item = char>> Segments<Untagged<I>> {
+    pub fn new(chars: I, boundary: &Boundary) -> Self {
+        Self::tagged(chars.map(|char| (char, ())), boundary)
+    }
+}
+
+impl<I: Iterator<Item = (char, T)>, T> Segments<I, T> {
+    pub fn tagged(chars: I, boundary: &Boundary) -> Self {
+        let separator = match boundary {
+            Boundary::Separator(separator) => separator.chars().collect(),
+            _ => Vec::new(),
+        };
+        Self {
+            chars,
+            boundary: boundary.clone(),
+            separator,
+            pending: VecDeque::new(),
+        }
+    }
+
+    fn next_char(&mut self) -> Option<(char, T)> {
+        self.pending.pop_front().or_else(|| self.chars.next())
+    }
+}
+
+impl<I: Iterator<Item = (char, T)>, T> Iterator for Segments<I, T> {
+    type Item = Token<T>;
+
+    fn next(&mut self) -> Option<Token<T>> {
+        match self.boundary {
+            Boundary::None => self.chars.next().map(|(char, tag)| Token::Char(char, tag)),
+            Boundary::Newline => self.chars.next().map(|(char, tag)| match char {
+                '\n' => Token::Break,
+                char => Token::Char(char, tag),
+            }),
+            Boundary::BlankLine => {
+                let (char, tag) = self.next_char()?;
+                if char != '\n' {
+                    return Some(Token::Char(char, tag));
+                }
+                let mut newlines = 1;
+                loop {
+                    match self.chars.next() {
+                        Some(('\n', _)) => newlines += 1,
+                        Some(next) => break self.pending.push_back(next),
+                        None => break,
+                    }
+                }
+                match newlines {
+                    1 => Some(Token::Char('\n', tag)),
+                    _ => Some(Token::Break),
+                }
+            }
+            Boundary::Separator(_) if self.separator.is_empty() => {
+                self.chars.next().map(|(char, tag)| Token::Char(char, tag))
+            }
+            Boundary::Separator(_) => loop {
+                let prefix = self
+                    .pending
+                    .iter()
+                    .zip(&self.separator)
+                    .all(|((a, _), b)| a == b);
+                if prefix && self.pending.len() == self.separator.len() {
+                    self.pending.clear();
+                    return Some(Token::Break);
+                }
+                if prefix {
+                    if let Some(next) = self.chars.next() {
+                        self.pending.push_back(next);
+                        continue;
+                    }
+                }
+                return self
+                    .pending
+                    .pop_front()
+                    .map(|(char, tag)| Token::Char(char, tag));
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(text: &str, boundary: Boundary) -> Vec<String> {
+        let mut res = vec![String::new()];
+        for token in Segments::new(text.chars(), &boundary) {
+            match token {
+                Token::Char(char, _) => res.last_mut().unwrap().push(char),
+                Token::Break => res.push(String::new()),
+            }
+        }
+        res
+    }
+
+    #[test]
+    pub fn newlines() {
+        let text = "a\nb\n\n\nc\n";
+        assert_eq!(
+            segments(text, Boundary::Newline),
+            ["a", "b", "", "", "c", ""]
+        );
+        assert_eq!(segments(text, Boundary::BlankLine), ["a\nb", "c\n"]);
+    }
+
+    #[test]
+    pub fn separator() {
+        let boundary = Boundary::Separator("<>".to_string());
+        assert_eq!(segments("a<<>b<>", boundary.clone()), ["a<", "b", ""]);
+        assert_eq!(segments("<a>", boundary), ["<a>"]);
+        let boundary = Boundary::Separator("aa".to_string());
+        assert_eq!(segments("aaab", boundary), ["", "ab"]);
+    }
+}
@@ -0,0 +1,243 @@
+use super::{Boundary, NgramData, Ngrams, Normalization};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{self, Display, Formatter};
+
+/// Compact form of [`NgramData`]. Characters are interned into a small
+/// alphabet, ngrams are packed into integers and stored in sorted
+/// vectors, which takes less memory and makes contraction faster.
+///
+/// Ngrams have to fit in 64 bits, so `N` times the number of bits
+/// needed to index the alphabet can't exceed 64.
+///
+/// # Example
+///
+/// ```
+/// use algae_lib::{CompactNgramData, NgramData};
+/// let corpus = NgramData::<3>::new("Quick brown fox".chars());
+/// let compact = CompactNgramData::try_from(&corpus).unwrap();
+/// assert_eq!(compact.ngrams::<2>(), corpus.ngrams::<2>());
+/// assert_eq!(NgramData::from(&compact), corpus);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompactNgramData<const N: usize> {
+    // Sorted, so packed ngrams sort like the ngrams themselves.
+    alphabet: Vec<char>,
+    bits: usize,
+    ngrams: Vec<(u64, u64)>,
+    tails: Vec<(u64, u64)>,
+    heads: Vec<(u64, u64)>,
+    // Lengths of short texts are stored with them.
+    short: Vec<(usize, u64, u64)>,
+    normalization: Normalization,
+    boundary: Boundary,
+    contraction_only: bool,
+}
+
+impl<const N: usize> CompactNgramData<N> {
+    /// Characters occurring in the data in ascending order.
+    pub fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    /// Calculates the number of ngrams of length `K`. Result is the same
+    /// as the one of [`NgramData::ngrams`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if N < K.
+    pub fn ngrams<const K: usize>(&self) -> Ngrams<K> {
+        assert!(K <= N);
+        // Prefixes of sorted ngrams are sorted too, so equal ones are
+        // next to each other.
+        let mut prefixes: Vec<(u64, u64)> = Vec::new();
+        for &(ngram, count) in &self.ngrams {
+            let prefix = shr(ngram, (N - K) * self.bits);
+            match prefixes.last_mut() {
+                Some((last, total)) if *last == prefix => *total += count,
+                _ => prefixes.push((prefix, count)),
+            }
+        }
+        let texts = self.tails.iter().map(|&(tail, count)| (N, tail, count));
+        let mut res = HashMap::with_capacity(prefixes.len());
+        for (len, text, count) in texts.chain(self.short.iter().copied()) {
+            for i in (0..(len + 1).saturating_sub(K)).rev() {
+                let window = shr(text, i * self.bits) & mask(K * self.bits);
+                *res.entry(self.unpack(window)).or_insert(0) += count;
+            }
+        }
+        for (prefix, count) in prefixes {
+            *res.entry(self.unpack(prefix)).or_insert(0) += count;
+        }
+        res
+    }
+
+    fn pack(&self, chars: &[char]) -> u64 {
+        chars.iter().fold(0, |acc, char| {
+            let i = self.alphabet.binary_search(char).unwrap();
+            (acc << self.bits) | i as u64
+        })
+    }
+
+    fn unpack<const K: usize>(&self, packed: u64) -> [char; K] {
+        let mut res = ['\0'; K];
+        self.unpack_into(packed, &mut res);
+        res
+    }
+
+    fn unpack_into(&self, mut packed: u64, chars: &mut [char]) {
+        for char in chars.iter_mut().rev() {
+            *char = self.alphabet[(packed & mask(self.bits)) as usize];
+            packed = shr(packed, self.bits);
+        }
+    }
+
+    fn pack_all(&self, counts: &Ngrams<N>) -> Vec<(u64, u64)> {
+        let mut res: Vec<_> = counts
+            .iter()
+            .map(|(ngram, &count)| (self.pack(ngram), count))
+            .collect();
+        res.sort_unstable();
+        res
+    }
+
+    fn unpack_all(&self, counts: &[(u64, u64)]) -> Ngrams<N> {
+        counts
+            .iter()
+            .map(|&(ngram, count)| (self.unpack(ngram), count))
+            .collect()
+    }
+}
+
+// Shifts right, giving 0 when every bit is shifted out.
+fn shr(value: u64, bits: usize) -> u64 {
+    value.checked_shr(bits as u32).unwrap_or(0)
+}
+
+fn mask(bits: usize) -> u64 {
+    match bits {
+        64.. => u64::MAX,
+        bits => (1 << bits) - 1,
+    }
+}
+
+impl<const N: usize> TryFrom<&NgramData<N>> for CompactNgramData<N> {
+    type Error = AlphabetTooLarge;
+
+    fn try_from(data: &NgramData<N>) -> Result<Self, AlphabetTooLarge> {
+        let alphabet: BTreeSet<char> = data
+            .ngrams
+            .keys()
+            .chain(data.tails.keys())
+            .chain(data.heads.keys())
+            .flatten()
+            .chain(data.short.keys().flatten())
+            .copied()
+            .collect();
+        let alphabet: Vec<_> = alphabet.into_iter().collect();
+        let bits = (usize::BITS - alphabet.len().saturating_sub(1).leading_zeros()).max(1);
+        let bits = bits as usize;
+        if N * bits > 64 {
+            return Err(AlphabetTooLarge {
+                alphabet: alphabet.len(),
+                len: N,
+            });
+        }
+        let mut res = Self {
+            alphabet,
+            bits,
+            ngrams: Vec::new(),
+            tails: Vec::new(),
+            heads: Vec::new(),
+            short: Vec::new(),
+            normalization: data.normalization,
+            boundary: data.boundary.clone(),
+            contraction_only: data.contraction_only,
+        };
+        res.ngrams = res.pack_all(&data.ngrams);
+        res.tails = res.pack_all(&data.tails);
+        res.heads = res.pack_all(&data.heads);
+        res.short = data
+            .short
+            .iter()
+            .map(|(short, &count)| (short.len(), res.pack(short), count))
+            .collect();
+        res.short.sort_unstable();
+        Ok(res)
+    }
+}
+
+impl<const N: usize> TryFrom<NgramData<N>> for CompactNgramData<N> {
+    type Error = AlphabetTooLarge;
+
+    fn try_from(data: NgramData<N>) -> Result<Self, AlphabetTooLarge> {
+        Self::try_from(&data)
+    }
+}
+
+impl<const N: usize> From<&CompactNgramData<N>> for NgramData<N> {
+    fn from(data: &CompactNgramData<N>) -> Self {
+        Self {
+            ngrams: data.unpack_all(&data.ngrams),
+            tails: data.unpack_all(&data.tails),
+            heads: data.unpack_all(&data.heads),
+            short: data
+                .short
+                .iter()
+                .map(|&(len, packed, count)| {
+                    let mut short = vec!['\0'; len];
+                    data.unpack_into(packed, &mut short);
+                    (short, count)
+                })
+                .collect(),
+            normalization: data.normalization,
+            boundary: data.boundary.clone(),
+            contraction_only: data.contraction_only,
+        }
+    }
+}
+
+impl<const N: usize> From<CompactNgramData<N>> for NgramData<N> {
+    fn from(data: CompactNgramData<N>) -> Self {
+        Self::from(&data)
+    }
+}
+
+/// Error returned when ngrams can't be packed into 64 bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AlphabetTooLarge {
+    /// Number of distinct characters.
+    pub alphabet: usize,
+    /// Length of ngrams.
+    pub len: usize,
+}
+
+impl Display for AlphabetTooLarge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let AlphabetTooLarge { alphabet, len } = self;
+        write!(
+            f,
+            "{len}-grams of {alphabet} characters don't fit in 64 bits"
+        )
+    }
+}
+
+impl std::error::Error for AlphabetTooLarge {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn round_trip() {
+        let documents = ["Zażółć gęślą jaźń", "quick brown fox", "ab", "a"];
+        let corpus = NgramData::<4>::from_documents(documents.map(str::chars));
+        let compact = CompactNgramData::try_from(&corpus).unwrap();
+        assert_eq!(NgramData::from(&compact), corpus);
+        assert_eq!(compact.ngrams::<4>(), corpus.ngrams::<4>());
+        assert_eq!(compact.ngrams::<2>(), corpus.ngrams::<2>());
+        assert_eq!(compact.ngrams::<1>(), corpus.ngrams::<1>());
+        let text: String = ('a'..='z').collect();
+        let corpus = NgramData::<14>::new(text.chars());
+        assert!(CompactNgramData::try_from(corpus).is_err());
+    }
+}
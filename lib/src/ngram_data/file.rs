@@ -0,0 +1,588 @@
+use super::{Boundary, NgramData, Normalization, UnicodeForm};
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{self, BufReader, ErrorKind, Read, Write};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+const MAGIC: &[u8; 8] = b"ALGAENGR";
+const VERSION: u32 = 1;
+
+/// Describes how ngram data stored in a file was made. Data is loaded
+/// only if it was made the same way.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileInfo {
+    pub normalization: Normalization,
+    pub boundary: Boundary,
+    /// Name of the expansion applied to the data, empty if there was none.
+    pub expansion: String,
+    pub source: SourceInfo,
+}
+
+/// Identifies the source text ngram data was calculated from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SourceInfo {
+    /// Checksum of the source text calculated with [`checksum`]. Only
+    /// this field is compared when loading data.
+    pub checksum: u64,
+    /// Size of the source file in bytes.
+    pub size: u64,
+    /// Modification time of the source file in nanoseconds since Unix
+    /// epoch, 0 if unknown.
+    pub modified: u64,
+}
+
+/// Calculates 64-bit FNV-1a hash of everything read from `reader`.
+///
+/// # Example
+///
+/// ```
+/// use algae_lib::checksum;
+/// assert_eq!(checksum(&b""[..]).unwrap(), 0xcbf29ce484222325);
+/// assert_ne!(checksum(&b"a"[..]).unwrap(), checksum(&b"b"[..]).unwrap());
+/// ```
+pub fn checksum(mut reader: impl Read) -> io::Result<u64> {
+    let mut hash = Fnv::new();
+    let mut buffer = vec![0; 1 << 16];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(hash.0),
+            Ok(read) => hash.write(&buffer[..read]),
+            Err(error) if error.kind() == ErrorKind::Interrupted => (),
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+impl SourceInfo {
+    /// Describes the file at `path`. Checksum of `previous` is reused if
+    /// the file has the same size and modification time, otherwise the
+    /// whole file is read to calculate it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading the file or its metadata fails.
+    pub fn of_file(path: impl AsRef<Path>, previous: Option<&SourceInfo>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let metadata = file.metadata()?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_nanos() as u64);
+        let mut res = Self {
+            checksum: 0,
+            size: metadata.len(),
+            modified,
+        };
+        match previous {
+            Some(previous)
+                if modified != 0 && previous.size == res.size && previous.modified == modified =>
+            {
+                res.checksum = previous.checksum;
+            }
+            _ => res.checksum = checksum(BufReader::new(file))?,
+        }
+        Ok(res)
+    }
+}
+
+impl FileInfo {
+    /// Reads only the header of a file written with [`NgramData::save`],
+    /// e.g. to get [`SourceInfo`] of the data without loading all of it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails or the header isn't valid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{FileInfo, NgramData, SourceInfo};
+    /// let corpus = NgramData::<3>::new("Quick fox".chars());
+    /// let source = SourceInfo {
+    ///     checksum: 1,
+    ///     ..SourceInfo::default()
+    /// };
+    /// let mut file = Vec::new();
+    /// corpus.save(&mut file, "", source).unwrap();
+    /// assert_eq!(FileInfo::read(&file[..]).unwrap().source, source);
+    /// ```
+    pub fn read(mut reader: impl Read) -> Result<Self, FormatError> {
+        let header = read_header(&mut reader)?;
+        let (_, info, _) = Bytes(&header).header().ok_or(FormatError::Corrupted)?;
+        Ok(info)
+    }
+}
+
+impl<const N: usize> NgramData<N> {
+    /// Writes `NgramData` to `writer` together with a header describing
+    /// it. `expansion` names the expansion applied to the data and
+    /// `source` identifies its source text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{checksum, FileInfo, NgramData, SourceInfo};
+    /// let text = "Quick fox";
+    /// let corpus = NgramData::<3>::new(text.chars());
+    /// let source = SourceInfo {
+    ///     checksum: checksum(text.as_bytes()).unwrap(),
+    ///     ..SourceInfo::default()
+    /// };
+    /// let mut file = Vec::new();
+    /// corpus.save(&mut file, "", source).unwrap();
+    /// let info = FileInfo {
+    ///     source,
+    ///     ..FileInfo::default()
+    /// };
+    /// assert_eq!(NgramData::load(&file[..], &info).unwrap(), corpus);
+    /// ```
+    pub fn save(
+        &self,
+        mut writer: impl Write,
+        expansion: &str,
+        source: SourceInfo,
+    ) -> io::Result<()> {
+        let mut payload = Vec::new();
+        put_counts(&mut payload, &self.ngrams, |b, ngram| put_chars(b, ngram));
+        put_counts(&mut payload, &self.tails, |b, tail| put_chars(b, tail));
+        put_counts(&mut payload, &self.heads, |b, head| put_chars(b, head));
+        put_counts(&mut payload, &self.short, |b, short| {
+            put_u32(b, short.len() as u32);
+            put_chars(b, short);
+        });
+        let mut info = Vec::new();
+        put_u32(&mut info, N as u32);
+        put_normalization(&mut info, &self.normalization);
+        put_boundary(&mut info, &self.boundary);
+        info.push(self.contraction_only as u8);
+        put_str(&mut info, expansion);
+        put_u64(&mut info, source.checksum);
+        put_u64(&mut info, source.size);
+        put_u64(&mut info, source.modified);
+        let mut header = Vec::new();
+        header.extend(MAGIC);
+        put_u32(&mut header, VERSION);
+        put_u32(&mut header, info.len() as u32);
+        header.extend(info);
+        put_u64(&mut header, payload.len() as u64);
+        let mut hash = Fnv::new();
+        hash.write(&payload);
+        put_u64(&mut header, hash.0);
+        writer.write_all(&header)?;
+        writer.write_all(&payload)?;
+        writer.flush()
+    }
+
+    /// Reads `NgramData` written with [`NgramData::save`]. Source text is
+    /// compared only by [`SourceInfo::checksum`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails, data isn't valid or it doesn't
+    /// match `expected`.
+    pub fn load(mut reader: impl Read, expected: &FileInfo) -> Result<Self, FormatError> {
+        let header = read_header(&mut reader)?;
+        let (len, info, contraction_only) =
+            Bytes(&header).header().ok_or(FormatError::Corrupted)?;
+        if len != N {
+            return Err(FormatError::DifferentLength(len));
+        }
+        if info.normalization != expected.normalization || info.boundary != expected.boundary {
+            return Err(FormatError::DifferentSettings);
+        }
+        if info.expansion != expected.expansion {
+            return Err(FormatError::DifferentExpansion(info.expansion));
+        }
+        if info.source.checksum != expected.source.checksum {
+            return Err(FormatError::SourceChanged);
+        }
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).map_err(FormatError::Io)?;
+        let mut bytes = Bytes(&bytes);
+        let len = bytes.u64().ok_or(FormatError::Corrupted)?;
+        let payload_checksum = bytes.u64().ok_or(FormatError::Corrupted)?;
+        let mut hash = Fnv::new();
+        hash.write(bytes.0);
+        if bytes.0.len() as u64 != len || hash.0 != payload_checksum {
+            return Err(FormatError::Corrupted);
+        }
+        let mut read_payload = || {
+            Some(Self {
+                ngrams: bytes.counts(|b| b.ngram())?,
+                tails: bytes.counts(|b| b.ngram())?,
+                heads: bytes.counts(|b| b.ngram())?,
+                short: bytes.counts(|b| {
+                    let len = b.u32()?;
+                    (0..len).map(|_| b.char()).collect()
+                })?,
+                normalization: info.normalization,
+                boundary: info.boundary.clone(),
+                contraction_only,
+            })
+        };
+        read_payload().ok_or(FormatError::Corrupted)
+    }
+}
+
+// Reads magic bytes and version, then returns the header that follows.
+fn read_header(reader: &mut impl Read) -> Result<Vec<u8>, FormatError> {
+    let mut prefix = [0; MAGIC.len() + 8];
+    match reader.read_exact(&mut prefix) {
+        Ok(()) => (),
+        Err(error) if error.kind() == ErrorKind::UnexpectedEof => {
+            return Err(FormatError::NotNgramData)
+        }
+        Err(error) => return Err(FormatError::Io(error)),
+    }
+    let mut bytes = Bytes(&prefix);
+    if bytes.take(MAGIC.len()) != Some(&MAGIC[..]) {
+        return Err(FormatError::NotNgramData);
+    }
+    let version = bytes.u32().unwrap();
+    if version != VERSION {
+        return Err(FormatError::UnsupportedVersion(version));
+    }
+    let len = bytes.u32().unwrap() as u64;
+    let mut header = Vec::new();
+    reader
+        .take(len)
+        .read_to_end(&mut header)
+        .map_err(FormatError::Io)?;
+    if header.len() as u64 != len {
+        return Err(FormatError::Corrupted);
+    }
+    Ok(header)
+}
+
+// 64-bit FNV-1a hash.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+fn put_u32(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend(value.to_le_bytes());
+}
+
+fn put_u64(buffer: &mut Vec<u8>, value: u64) {
+    buffer.extend(value.to_le_bytes());
+}
+
+fn put_str(buffer: &mut Vec<u8>, str: &str) {
+    put_u32(buffer, str.len() as u32);
+    buffer.extend(str.as_bytes());
+}
+
+fn put_chars(buffer: &mut Vec<u8>, chars: &[char]) {
+    for &char in chars {
+        put_u32(buffer, char as u32);
+    }
+}
+
+fn put_counts<T>(buffer: &mut Vec<u8>, counts: &HashMap<T, u64>, put: impl Fn(&mut Vec<u8>, &T)) {
+    put_u64(buffer, counts.len() as u64);
+    for (ngram, &count) in counts {
+        put(buffer, ngram);
+        put_u64(buffer, count);
+    }
+}
+
+fn put_normalization(buffer: &mut Vec<u8>, normalization: &Normalization) {
+    let form = match normalization.form {
+        None => 0,
+        Some(UnicodeForm::Nfc) => 1,
+        Some(UnicodeForm::Nfd) => 2,
+    };
+    buffer.extend([
+        normalization.line_endings as u8,
+        normalization.lowercase as u8,
+        normalization.case_fold as u8,
+        form,
+        normalization.ascii_punctuation as u8,
+        normalization.drop_control as u8,
+        normalization.collapse_whitespace as u8,
+    ]);
+}
+
+fn put_boundary(buffer: &mut Vec<u8>, boundary: &Boundary) {
+    match boundary {
+        Boundary::None => buffer.push(0),
+        Boundary::Newline => buffer.push(1),
+        Boundary::BlankLine => buffer.push(2),
+        Boundary::Separator(separator) => {
+            buffer.push(3);
+            put_str(buffer, separator);
+        }
+    }
+}
+
+// Reads values from the front of a slice.
+struct Bytes<'a>(&'a [u8]);
+
+impl<'a> Bytes<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> Option<bool> {
+        match self.u8()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn str(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+
+    fn char(&mut self) -> Option<char> {
+        char::from_u32(self.u32()?)
+    }
+
+    fn ngram<const N: usize>(&mut self) -> Option<[char; N]> {
+        let mut ngram = ['\0'; N];
+        for char in &mut ngram {
+            *char = self.char()?;
+        }
+        Some(ngram)
+    }
+
+    fn counts<T: Eq + Hash>(
+        &mut self,
+        read: impl Fn(&mut Self) -> Option<T>,
+    ) -> Option<HashMap<T, u64>> {
+        let len = self.u64()?;
+        let mut counts = HashMap::new();
+        for _ in 0..len {
+            let ngram = read(self)?;
+            counts.insert(ngram, self.u64()?);
+        }
+        Some(counts)
+    }
+
+    fn normalization(&mut self) -> Option<Normalization> {
+        Some(Normalization {
+            line_endings: self.bool()?,
+            lowercase: self.bool()?,
+            case_fold: self.bool()?,
+            form: match self.u8()? {
+                0 => None,
+                1 => Some(UnicodeForm::Nfc),
+                2 => Some(UnicodeForm::Nfd),
+                _ => return None,
+            },
+            ascii_punctuation: self.bool()?,
+            drop_control: self.bool()?,
+            collapse_whitespace: self.bool()?,
+        })
+    }
+
+    // Length of ngrams, info and whether data is contraction only.
+    fn header(&mut self) -> Option<(usize, FileInfo, bool)> {
+        let len = self.u32()? as usize;
+        let normalization = self.normalization()?;
+        let boundary = self.boundary()?;
+        let contraction_only = self.bool()?;
+        let info = FileInfo {
+            normalization,
+            boundary,
+            expansion: self.str()?,
+            source: SourceInfo {
+                checksum: self.u64()?,
+                size: self.u64()?,
+                modified: self.u64()?,
+            },
+        };
+        Some((len, info, contraction_only))
+    }
+
+    fn boundary(&mut self) -> Option<Boundary> {
+        match self.u8()? {
+            0 => Some(Boundary::None),
+            1 => Some(Boundary::Newline),
+            2 => Some(Boundary::BlankLine),
+            3 => Some(Boundary::Separator(self.str()?)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FormatError {
+    Io(io::Error),
+    /// File doesn't start with the expected magic bytes. Caches written
+    /// before the format had a header fail this way too, so they can be
+    /// recalculated.
+    NotNgramData,
+    /// File was written in a format version this library can't read.
+    UnsupportedVersion(u32),
+    /// File contains ngrams of given length.
+    DifferentLength(usize),
+    /// Data was normalized or split into documents differently.
+    DifferentSettings,
+    /// Data was expanded with the given expansion.
+    DifferentExpansion(String),
+    /// Data was calculated from a different source text.
+    SourceChanged,
+    /// File is truncated or its checksum doesn't match.
+    Corrupted,
+}
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use FormatError::*;
+        match self {
+            Io(error) => write!(f, "failed to read ngram data: {error}"),
+            NotNgramData => write!(f, "file doesn't contain ngram data"),
+            UnsupportedVersion(version) => write!(f, "unsupported format version ({version})"),
+            DifferentLength(len) => write!(f, "file contains ngrams of different length ({len})"),
+            DifferentSettings => write!(f, "ngrams were normalized or split differently"),
+            DifferentExpansion(expansion) => {
+                write!(f, "ngrams were expanded differently ({expansion:?})")
+            }
+            SourceChanged => write!(f, "source text has changed"),
+            Corrupted => write!(f, "ngram data is corrupted"),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FormatError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn round_trip() {
+        let text: String = ('a'..='z').cycle().take(100).collect();
+        let mut corpus = NgramData::<40>::empty().with_boundary(Boundary::Separator("xy".into()));
+        corpus.add(text.chars());
+        corpus.add("short".chars());
+        let info = FileInfo {
+            boundary: corpus.boundary().clone(),
+            expansion: "shift".into(),
+            ..FileInfo::default()
+        };
+        let mut file = Vec::new();
+        corpus
+            .save(&mut file, "shift", SourceInfo::default())
+            .unwrap();
+        assert_eq!(NgramData::load(&file[..], &info).unwrap(), corpus);
+        let result = NgramData::<3>::load(&file[..], &info);
+        assert!(matches!(result, Err(FormatError::DifferentLength(40))));
+        let last = file.len() - 1;
+        file[last] ^= 1;
+        let result = NgramData::<40>::load(&file[..], &info);
+        assert!(matches!(result, Err(FormatError::Corrupted)));
+    }
+
+    #[test]
+    pub fn mismatches() {
+        let corpus = NgramData::<2>::new("ab".chars());
+        let mut file = Vec::new();
+        let source = SourceInfo {
+            checksum: 1,
+            size: 2,
+            modified: 3,
+        };
+        corpus.save(&mut file, "", source).unwrap();
+        let load = |info: &FileInfo| NgramData::<2>::load(&file[..], info);
+        let info = FileInfo {
+            source,
+            ..FileInfo::default()
+        };
+        assert_eq!(FileInfo::read(&file[..]).unwrap(), info);
+        // Only the checksum has to match.
+        let touched = FileInfo {
+            source: SourceInfo {
+                modified: 4,
+                ..source
+            },
+            ..info.clone()
+        };
+        assert!(load(&touched).is_ok());
+        let changed = FileInfo {
+            source: SourceInfo {
+                checksum: 2,
+                ..source
+            },
+            ..info.clone()
+        };
+        assert!(matches!(load(&changed), Err(FormatError::SourceChanged)));
+        let expanded = FileInfo {
+            expansion: "shift".into(),
+            ..info.clone()
+        };
+        assert!(matches!(
+            load(&expanded),
+            Err(FormatError::DifferentExpansion(_))
+        ));
+        let result = NgramData::<2>::load(&b"not ngrams"[..], &info);
+        assert!(matches!(result, Err(FormatError::NotNgramData)));
+    }
+
+    #[test]
+    pub fn source_info() {
+        let path = std::env::temp_dir().join(format!("algae-source-{}", std::process::id()));
+        std::fs::write(&path, "Quick fox").unwrap();
+        let info = SourceInfo::of_file(&path, None).unwrap();
+        assert_eq!(info.checksum, checksum(&b"Quick fox"[..]).unwrap());
+        assert_eq!(info.size, 9);
+        // Checksum of an unchanged file isn't recalculated.
+        let previous = SourceInfo {
+            checksum: 1,
+            ..info
+        };
+        assert_eq!(
+            SourceInfo::of_file(&path, Some(&previous))
+                .unwrap()
+                .checksum,
+            1
+        );
+        let resized = SourceInfo {
+            size: 8,
+            ..previous
+        };
+        assert_eq!(SourceInfo::of_file(&path, Some(&resized)).unwrap(), info);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,200 @@
+use super::NgramData;
+use std::cell::OnceCell;
+use std::collections::HashMap;
+
+/// Index answering queries about ngrams of [`NgramData`] of any length
+/// up to `N`. Ngrams of every length are contracted once, on the first
+/// query about them, and kept for the following ones.
+///
+/// # Example
+///
+/// ```
+/// use algae_lib::NgramData;
+/// let corpus = NgramData::<3>::new("the then they".chars());
+/// let index = corpus.index();
+/// assert_eq!(index.count(&['t', 'h']), 3);
+/// assert_eq!(index.count(&['h', 'e', 'n']), 1);
+/// let after_he: Vec<_> = index.with_prefix(3, &['h', 'e']).map(|(ngram, _)| ngram).collect();
+/// assert_eq!(after_he, [['h', 'e', ' '], ['h', 'e', 'n'], ['h', 'e', 'y']]);
+/// ```
+pub struct NgramIndex<'a, const N: usize> {
+    data: &'a NgramData<N>,
+    views: Vec<OnceCell<View>>,
+}
+
+// Ngrams of a single length.
+#[derive(Default)]
+struct View {
+    counts: HashMap<Vec<char>, u64>,
+    // Sorted lazily, as some queries don't need them.
+    sorted: OnceCell<Vec<(Vec<char>, u64)>>,
+    sorted_by_suffix: OnceCell<Vec<(Vec<char>, u64)>>,
+    by_frequency: OnceCell<Vec<(Vec<char>, u64)>>,
+}
+
+impl View {
+    fn sorted_by(
+        &self,
+        cmp: impl Fn(&[char], &[char]) -> std::cmp::Ordering,
+    ) -> Vec<(Vec<char>, u64)> {
+        let mut res: Vec<_> = self
+            .counts
+            .iter()
+            .map(|(ngram, &count)| (ngram.clone(), count))
+            .collect();
+        res.sort_unstable_by(|(ngram1, _), (ngram2, _)| cmp(ngram1, ngram2));
+        res
+    }
+}
+
+impl<const N: usize> NgramData<N> {
+    /// Creates an index of ngrams of `self`, see [`NgramIndex`].
+    pub fn index(&self) -> NgramIndex<'_, N> {
+        NgramIndex {
+            data: self,
+            views: (0..N).map(|_| OnceCell::new()).collect(),
+        }
+    }
+}
+
+impl<'a, const N: usize> NgramIndex<'a, N> {
+    fn view(&self, len: usize) -> &View {
+        assert!(
+            (1..=N).contains(&len),
+            "ngrams have to be between 1 and {N} characters long"
+        );
+        self.views[len - 1].get_or_init(|| View {
+            counts: self.data.contract(len),
+            ..View::default()
+        })
+    }
+
+    /// Counts of ngrams of length `len`. They are the same as the ones
+    /// returned by [`NgramData::ngrams`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `len` is 0 or greater than N.
+    pub fn counts(&self, len: usize) -> &HashMap<Vec<char>, u64> {
+        &self.view(len).counts
+    }
+
+    /// Number of occurrences of `ngram`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `ngram` is empty or longer than N.
+    pub fn count(&self, ngram: &[char]) -> u64 {
+        self.counts(ngram.len()).get(ngram).copied().unwrap_or(0)
+    }
+
+    /// Ngrams of length `len` starting with `prefix` with their counts,
+    /// in ascending order.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `len` is 0 or greater than N.
+    pub fn with_prefix<'b>(
+        &'b self,
+        len: usize,
+        prefix: &'b [char],
+    ) -> impl Iterator<Item = (&'b [char], u64)> + 'b {
+        let view = self.view(len);
+        let sorted = view.sorted.get_or_init(|| view.sorted_by(|a, b| a.cmp(b)));
+        let start = sorted.partition_point(|(ngram, _)| ngram.as_slice() < prefix);
+        sorted[start..]
+            .iter()
+            .take_while(move |(ngram, _)| ngram.starts_with(prefix))
+            .map(|(ngram, count)| (&ngram[..], *count))
+    }
+
+    /// Ngrams of length `len` ending with `suffix` with their counts,
+    /// in ascending order of their reversed characters.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `len` is 0 or greater than N.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let corpus = NgramData::<2>::new("ab cb bc".chars());
+    /// let index = corpus.index();
+    /// let ngrams: Vec<_> = index.with_suffix(2, &['b']).map(|(ngram, _)| ngram).collect();
+    /// assert_eq!(ngrams, [&[' ', 'b'], &['a', 'b'], &['c', 'b']]);
+    /// ```
+    pub fn with_suffix<'b>(
+        &'b self,
+        len: usize,
+        suffix: &'b [char],
+    ) -> impl Iterator<Item = (&'b [char], u64)> + 'b {
+        let view = self.view(len);
+        let sorted = view
+            .sorted_by_suffix
+            .get_or_init(|| view.sorted_by(|a, b| a.iter().rev().cmp(b.iter().rev())));
+        let start = sorted.partition_point(|(ngram, _)| ngram.iter().rev().lt(suffix.iter().rev()));
+        sorted[start..]
+            .iter()
+            .take_while(move |(ngram, _)| ngram.ends_with(suffix))
+            .map(|(ngram, count)| (&ngram[..], *count))
+    }
+
+    /// Ngrams of length `len` with their counts, from the most frequent.
+    /// Ngrams with the same count are in ascending order.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `len` is 0 or greater than N.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let corpus = NgramData::<2>::new("abcabd".chars());
+    /// let index = corpus.index();
+    /// let top: Vec<_> = index.by_frequency(2).take(2).collect();
+    /// assert_eq!(top, [(&['a', 'b'][..], 2), (&['b', 'c'][..], 1)]);
+    /// ```
+    pub fn by_frequency(&self, len: usize) -> impl Iterator<Item = (&[char], u64)> + '_ {
+        let view = self.view(len);
+        let sorted = view.by_frequency.get_or_init(|| {
+            let mut res = view.sorted_by(|a, b| a.cmp(b));
+            res.sort_by(|(_, count1), (_, count2)| count2.cmp(count1));
+            res
+        });
+        sorted.iter().map(|(ngram, count)| (&ngram[..], *count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn queries() {
+        let documents = ["Quick brown fox", "jumps over", "a", "lazy dog"];
+        let corpus = NgramData::<3>::from_documents(documents.map(str::chars));
+        let index = corpus.index();
+        let bigrams: HashMap<_, _> = corpus
+            .ngrams::<2>()
+            .into_iter()
+            .map(|(ngram, count)| (ngram.to_vec(), count))
+            .collect();
+        assert_eq!(index.counts(2), &bigrams);
+        assert_eq!(index.count(&['o']), 4);
+        assert_eq!(index.count(&['o', 'x']), 1);
+        assert_eq!(index.count(&['x', 'j']), 0);
+        let prefixed: Vec<_> = index.with_prefix(3, &['o']).collect();
+        assert_eq!(
+            prefixed,
+            [(&['o', 'v', 'e'][..], 1), (&['o', 'w', 'n'][..], 1)]
+        );
+        assert_eq!(index.with_suffix(1, &['o']).count(), 1);
+        assert_eq!(index.with_prefix(2, &[]).count(), bigrams.len());
+        let frequencies: Vec<_> = index.by_frequency(1).collect();
+        assert_eq!(frequencies[0], (&[' '][..], 4));
+        assert_eq!(frequencies[1], (&['o'][..], 4));
+        assert!(frequencies.windows(2).all(|w| w[0].1 >= w[1].1));
+    }
+}
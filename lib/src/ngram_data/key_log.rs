@@ -0,0 +1,465 @@
+use super::boundary::{Segments, Token};
+use super::{Boundary, NgramData, Normalization};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, BufRead, BufReader, Read};
+
+/// What happened to a key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyAction {
+    Press,
+    Release,
+    /// Key is held long enough to be repeated.
+    Repeat,
+}
+
+/// Timestamped key event, like the ones reported by evdev.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyEvent {
+    /// Time in seconds.
+    pub time: f64,
+    /// Name of the key, e.g. `KEY_A`.
+    pub key: String,
+    pub action: KeyAction,
+}
+
+/// Format of key logs. Every event is in a separate line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Rows of `time,key,action`. A first row whose time isn't a number
+    /// is treated as a header.
+    Csv,
+    /// Objects with `time`, `key` and `action` fields.
+    Jsonl,
+}
+
+impl KeyEvent {
+    /// Reads key events from a log. Actions can be given as names
+    /// (`press`, `release`, `repeat`) or evdev values (`1`, `0`, `2`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails or an event is malformed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{KeyAction, KeyEvent, LogFormat};
+    /// let log = r#"{"time": 0.5, "key": "KEY_A", "action": 1}"#;
+    /// let events = KeyEvent::read(log.as_bytes(), LogFormat::Jsonl).unwrap();
+    /// assert_eq!(events[0].action, KeyAction::Press);
+    /// ```
+    pub fn read(reader: impl Read, format: LogFormat) -> Result<Vec<Self>, KeyLogError> {
+        let mut events = Vec::new();
+        for (i, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line.map_err(KeyLogError::Io)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event = match format {
+                LogFormat::Csv => parse_csv(&line),
+                LogFormat::Jsonl => parse_json(&line),
+            };
+            match event {
+                Some(event) => events.push(event),
+                None if i == 0 && format == LogFormat::Csv => (),
+                None => return Err(KeyLogError::InvalidEvent(i + 1)),
+            }
+        }
+        Ok(events)
+    }
+}
+
+fn parse_action(action: &str) -> Option<KeyAction> {
+    match action.trim() {
+        "press" | "1" => Some(KeyAction::Press),
+        "release" | "0" => Some(KeyAction::Release),
+        "repeat" | "2" => Some(KeyAction::Repeat),
+        _ => None,
+    }
+}
+
+fn parse_csv(line: &str) -> Option<KeyEvent> {
+    let mut fields = line.split(',');
+    let event = KeyEvent {
+        time: fields.next()?.trim().parse().ok()?,
+        key: fields.next()?.trim().to_string(),
+        action: parse_action(fields.next()?)?,
+    };
+    fields.next().is_none().then_some(event)
+}
+
+fn parse_json(line: &str) -> Option<KeyEvent> {
+    let value: Value = serde_json::from_str(line).ok()?;
+    let action = match &value["action"] {
+        Value::String(action) => parse_action(action)?,
+        Value::Number(action) => parse_action(&action.to_string())?,
+        _ => return None,
+    };
+    Some(KeyEvent {
+        time: value["time"].as_f64()?,
+        key: value["key"].as_str()?.to_string(),
+        action,
+    })
+}
+
+/// Describes how key events are turned into text.
+#[derive(Clone, Debug, Default)]
+pub struct KeyLog {
+    /// Symbols typed with keys. Presses of other keys are ignored, so
+    /// shortcuts and editor commands are counted only when their keys
+    /// have symbols.
+    pub keymap: HashMap<String, char>,
+    /// Pauses longer than this many seconds split the log into separate
+    /// documents.
+    pub idle: Option<f64>,
+}
+
+impl KeyLog {
+    /// Splits key presses into documents of typed symbols with times of
+    /// their presses. Releases and repeats are ignored.
+    pub fn documents(&self, events: &[KeyEvent]) -> Vec<Vec<(char, f64)>> {
+        let mut documents = vec![Vec::new()];
+        let mut last = None;
+        for event in events {
+            if event.action != KeyAction::Press {
+                continue;
+            }
+            // Presses of keys without symbols still mean that someone
+            // is typing.
+            let pause = match (last.replace(event.time), self.idle) {
+                (Some(last), Some(idle)) => event.time - last > idle,
+                _ => false,
+            };
+            if pause && !documents.last().unwrap().is_empty() {
+                documents.push(Vec::new());
+            }
+            if let Some(&symbol) = self.keymap.get(&event.key) {
+                documents.last_mut().unwrap().push((symbol, event.time));
+            }
+        }
+        documents.retain(|document| !document.is_empty());
+        documents
+    }
+}
+
+impl<const N: usize> NgramData<N> {
+    /// Counts ngrams of symbols typed in key events. Every document found
+    /// by [`KeyLog::documents`] is counted as if it was added with
+    /// [`NgramData::add`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{KeyEvent, KeyLog, LogFormat, NgramData};
+    /// let log = "time,key,action\n0.0,KEY_H,1\n0.1,KEY_H,0\n0.2,KEY_I,1\n";
+    /// let events = KeyEvent::read(log.as_bytes(), LogFormat::Csv).unwrap();
+    /// let key_log = KeyLog {
+    ///     keymap: [("KEY_H".into(), 'h'), ("KEY_I".into(), 'i')].into(),
+    ///     idle: None,
+    /// };
+    /// let mut corpus = NgramData::<2>::empty();
+    /// corpus.add_key_events(&events, &key_log);
+    /// assert_eq!(corpus, NgramData::new("hi".chars()));
+    /// ```
+    pub fn add_key_events(&mut self, events: &[KeyEvent], log: &KeyLog) {
+        for document in log.documents(events) {
+            self.add(document.into_iter().map(|(symbol, _)| symbol));
+        }
+    }
+}
+
+/// Statistics of time it took to type an ngram, from the first to the
+/// last press.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Timing {
+    pub count: u64,
+    sum: f64,
+    sum_of_squares: f64,
+}
+
+impl Timing {
+    fn add(&mut self, duration: f64) {
+        self.count += 1;
+        self.sum += duration;
+        self.sum_of_squares += duration * duration;
+    }
+
+    /// Mean time in seconds, `None` if nothing was timed.
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+
+    /// Standard deviation of time in seconds, `None` if nothing was
+    /// timed.
+    pub fn std_dev(&self) -> Option<f64> {
+        let mean = self.mean()?;
+        let variance = self.sum_of_squares / self.count as f64 - mean * mean;
+        Some(variance.max(0.0).sqrt())
+    }
+}
+
+/// Timing statistics of ngrams typed in key logs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Timings<const N: usize> {
+    timings: HashMap<[char; N], Timing>,
+    normalization: Normalization,
+    boundary: Boundary,
+}
+
+impl<const N: usize> Timings<N> {
+    /// Calculates timings of ngrams typed in key events, found the same
+    /// way as by [`NgramData::add_key_events`] with default settings.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{KeyEvent, KeyLog, LogFormat, Timings};
+    /// let log = "0.0,KEY_H,1\n0.2,KEY_I,1\n1.0,KEY_H,1\n1.4,KEY_I,1\n";
+    /// let events = KeyEvent::read(log.as_bytes(), LogFormat::Csv).unwrap();
+    /// let key_log = KeyLog {
+    ///     keymap: [("KEY_H".into(), 'h'), ("KEY_I".into(), 'i')].into(),
+    ///     idle: Some(0.5),
+    /// };
+    /// let timings = Timings::<2>::from_key_events(&events, &key_log);
+    /// let timing = timings.get(&['h', 'i']).unwrap();
+    /// assert_eq!(timing.count, 2);
+    /// assert!((timing.mean().unwrap() - 0.3).abs() < 1e-9);
+    /// assert_eq!(timings.get(&['i', 'h']), None);
+    /// ```
+    pub fn from_key_events(events: &[KeyEvent], log: &KeyLog) -> Self {
+        let mut res = Self::default();
+        res.add_key_events(events, log);
+        res
+    }
+
+    /// Sets normalization applied to typed symbols. It should be the
+    /// same as the one of `NgramData` counted from the same key log.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `self` isn't empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{KeyEvent, KeyLog, LogFormat, Normalization, Timings};
+    /// let log = "0.0,KEY_A,1\n0.2,KEY_B,1\n";
+    /// let events = KeyEvent::read(log.as_bytes(), LogFormat::Csv).unwrap();
+    /// let key_log = KeyLog {
+    ///     keymap: [("KEY_A".into(), 'A'), ("KEY_B".into(), 'b')].into(),
+    ///     idle: None,
+    /// };
+    /// let normalization = Normalization {
+    ///     lowercase: true,
+    ///     ..Normalization::default()
+    /// };
+    /// let mut timings = Timings::<2>::default().with_normalization(normalization);
+    /// timings.add_key_events(&events, &key_log);
+    /// assert_eq!(timings.get(&['a', 'b']).unwrap().count, 1);
+    /// ```
+    pub fn with_normalization(mut self, normalization: Normalization) -> Self {
+        assert!(
+            self.timings.is_empty(),
+            "tried to change settings of counted timings"
+        );
+        self.normalization = normalization;
+        self
+    }
+
+    /// Sets where typed symbols are split into separate documents, in
+    /// addition to pauses. It should be the same as the one of
+    /// `NgramData` counted from the same key log.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `self` isn't empty.
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self {
+        assert!(
+            self.timings.is_empty(),
+            "tried to change settings of counted timings"
+        );
+        self.boundary = boundary;
+        self
+    }
+
+    pub fn normalization(&self) -> &Normalization {
+        &self.normalization
+    }
+
+    pub fn boundary(&self) -> &Boundary {
+        &self.boundary
+    }
+
+    /// Adds timings of ngrams typed in key events. Symbols are
+    /// normalized and split into documents the same way as by
+    /// [`NgramData::add_key_events`]. A symbol made of several typed ones
+    /// is timed at the press of the last of them.
+    pub fn add_key_events(&mut self, events: &[KeyEvent], log: &KeyLog) {
+        for document in log.documents(events) {
+            let symbols = self.normalization.apply_tagged(document.into_iter());
+            let mut window = Vec::with_capacity(N);
+            for token in Segments::tagged(symbols, &self.boundary) {
+                match token {
+                    Token::Char(symbol, time) => {
+                        if window.len() == N {
+                            window.remove(0);
+                        }
+                        window.push((symbol, time));
+                    }
+                    Token::Break => {
+                        window.clear();
+                        continue;
+                    }
+                }
+                if window.len() == N {
+                    let ngram = std::array::from_fn(|i| window[i].0);
+                    let duration = window[N - 1].1 - window[0].1;
+                    self.timings.entry(ngram).or_default().add(duration);
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, ngram: &[char; N]) -> Option<&Timing> {
+        self.timings.get(ngram)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&[char; N], &Timing)> {
+        self.timings.iter()
+    }
+
+    /// Adds timings from `other` to `self`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `self` and `other` use different
+    /// normalization or boundary.
+    pub fn merge(&mut self, other: &Self) {
+        assert_eq!(
+            self.normalization, other.normalization,
+            "tried to merge timings with different normalization"
+        );
+        assert_eq!(
+            self.boundary, other.boundary,
+            "tried to merge timings with different boundary"
+        );
+        for (ngram, timing) in &other.timings {
+            let total = self.timings.entry(*ngram).or_default();
+            total.count += timing.count;
+            total.sum += timing.sum;
+            total.sum_of_squares += timing.sum_of_squares;
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum KeyLogError {
+    Io(io::Error),
+    /// Event at given line is malformed.
+    InvalidEvent(usize),
+}
+
+impl Display for KeyLogError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyLogError::Io(error) => write!(f, "failed to read key log: {error}"),
+            KeyLogError::InvalidEvent(line) => write!(f, "invalid key event at line {line}"),
+        }
+    }
+}
+
+impl std::error::Error for KeyLogError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KeyLogError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UnicodeForm;
+
+    #[test]
+    pub fn documents() {
+        let log = "\
+            0.0,KEY_LEFTCTRL,1\n\
+            0.1,KEY_S,1\n\
+            0.2,KEY_S,2\n\
+            0.3,KEY_LEFTSHIFT,1\n\
+            5.0,KEY_O,1\n\
+            5.1,KEY_K,1\n";
+        let events = KeyEvent::read(log.as_bytes(), LogFormat::Csv).unwrap();
+        let log = KeyLog {
+            keymap: [
+                ("KEY_LEFTCTRL", '⌃'),
+                ("KEY_S", 's'),
+                ("KEY_O", 'o'),
+                ("KEY_K", 'k'),
+            ]
+            .map(|(key, symbol)| (key.to_string(), symbol))
+            .into(),
+            idle: Some(1.0),
+        };
+        let documents: Vec<String> = log
+            .documents(&events)
+            .into_iter()
+            .map(|document| document.into_iter().map(|(symbol, _)| symbol).collect())
+            .collect();
+        assert_eq!(documents, ["⌃s", "ok"]);
+        let result = KeyEvent::read("0.0,KEY_A,1\n0.1,KEY_A\n".as_bytes(), LogFormat::Csv);
+        assert!(matches!(result, Err(KeyLogError::InvalidEvent(2))));
+    }
+
+    #[test]
+    pub fn timings_match_ngrams() {
+        let log = "\
+            0.0,KEY_E,1\n\
+            0.1,KEY_APOSTROPHE,1\n\
+            0.3,KEY_X,1\n\
+            0.4,KEY_ENTER,1\n\
+            0.5,KEY_E,1\n\
+            0.9,KEY_X,1\n";
+        let events = KeyEvent::read(log.as_bytes(), LogFormat::Csv).unwrap();
+        let log = KeyLog {
+            keymap: [
+                ("KEY_E", 'E'),
+                ("KEY_APOSTROPHE", '\u{301}'),
+                ("KEY_X", 'x'),
+                ("KEY_ENTER", '\n'),
+            ]
+            .map(|(key, symbol)| (key.to_string(), symbol))
+            .into(),
+            idle: None,
+        };
+        let normalization = Normalization {
+            lowercase: true,
+            form: Some(UnicodeForm::Nfc),
+            ..Normalization::default()
+        };
+        let mut corpus = NgramData::<2>::empty()
+            .with_normalization(normalization)
+            .with_boundary(Boundary::Newline);
+        corpus.add_key_events(&events, &log);
+        let mut timings = Timings::<2>::default()
+            .with_normalization(normalization)
+            .with_boundary(Boundary::Newline);
+        timings.add_key_events(&events, &log);
+        let mut timed: Vec<_> = timings
+            .iter()
+            .map(|(ngram, timing)| (*ngram, timing.count))
+            .collect();
+        timed.sort_unstable();
+        let mut counted: Vec<_> = corpus.ngrams::<2>().into_iter().collect();
+        counted.sort_unstable();
+        assert_eq!(timed, counted);
+        // Composed `é` is timed at the press of the accent.
+        let mean = timings.get(&['é', 'x']).unwrap().mean().unwrap();
+        assert!((mean - 0.2).abs() < 1e-9);
+        assert_eq!(Timing::default().mean(), None);
+    }
+}
@@ -0,0 +1,778 @@
+mod arithmetic;
+mod bootstrap;
+mod boundary;
+mod compact;
+mod file;
+mod index;
+mod key_log;
+mod normalization;
+mod parallel;
+mod prune;
+mod reader;
+mod source_code;
+mod stats;
+mod table;
+
+use super::{
+    expand_document, expand_end, expand_first, expand_full, expand_inner, expand_start, Context,
+};
+use boundary::{Segments, Token};
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::sync::Arc;
+
+pub use bootstrap::{Block, Blocks};
+pub use boundary::Boundary;
+pub use compact::{AlphabetTooLarge, CompactNgramData};
+pub use file::{checksum, FileInfo, FormatError, SourceInfo};
+pub use index::NgramIndex;
+pub use key_log::{KeyAction, KeyEvent, KeyLog, KeyLogError, LogFormat, Timing, Timings};
+pub use normalization::{Normalization, UnicodeForm};
+pub use prune::Pruned;
+pub use reader::InvalidBytes;
+pub use source_code::{CodeOptions, Language, SourceFiles};
+pub use stats::Coverage;
+pub use table::{TableError, TableFormat};
+
+type Ngrams<const N: usize> = HashMap<[char; N], u64>;
+
+/// Calculates ngrams of a corpora. Can be used to get accurate number of ngrams up to `N` long.
+///
+/// # Examples
+///
+/// Typical usage:
+/// ```
+/// use algae_lib::NgramData;
+///
+/// // Load the corpus
+/// let corpus = "Quick brown fox jumps over a lazy dog";
+/// let trigrams = NgramData::<3>::new(corpus.chars());
+///
+/// // Expand characters where needed. Here for example we
+/// // are expanding capital letters into a shift placeholder
+/// // and lowercase letter. Keep in mind that chosen
+/// // placeholder could occur naturally in the corpus.
+/// let trigrams: NgramData<3> = trigrams.expand(|char|
+///     if char.is_ascii_uppercase() {
+///         vec!['⇧', char.to_ascii_lowercase()]
+///     } else {
+///         vec![char]
+/// });
+///
+/// // Get raw ngrams
+/// let bigrams = trigrams.ngrams::<2>();
+/// let trigrams = trigrams.ngrams::<3>();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NgramData<const N: usize> {
+    // Last ngrams (tails) are stored separately from the rest to allow
+    // us to accurately calculate expansion results and shorter ngrams.
+    ngrams: Ngrams<N>,
+    tails: Ngrams<N>,
+    // First ngrams (heads) are counted again separately, so expansion
+    // can tell where documents start.
+    heads: Ngrams<N>,
+    // Texts shorter than `N` can't be stored as ngrams, so they are
+    // stored whole.
+    short: HashMap<Vec<char>, u64>,
+    normalization: Normalization,
+    boundary: Boundary,
+    // Set when ends of documents are unknown, e.g. for imported tables.
+    contraction_only: bool,
+}
+
+impl<const N: usize> NgramData<N> {
+    /// Creates empty `NgramData`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let corpus = NgramData::<3>::empty();
+    /// ```
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Makes `NgramData` normalize text before counting ngrams.
+    /// Normalization is stored with the data, so it can be checked when
+    /// the data is loaded from cache.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `self` isn't empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{NgramData, Normalization};
+    /// let normalization = Normalization {
+    ///     lowercase: true,
+    ///     ..Normalization::default()
+    /// };
+    /// let mut corpus = NgramData::<2>::empty().with_normalization(normalization);
+    /// corpus.add("Ab".chars());
+    /// assert_eq!(corpus.ngrams::<2>().get(&['a', 'b']), Some(&1));
+    /// assert_eq!(corpus.normalization(), &normalization);
+    /// ```
+    pub fn with_normalization(mut self, normalization: Normalization) -> Self {
+        assert!(
+            self.is_empty(),
+            "tried to change settings of counted ngrams"
+        );
+        self.normalization = normalization;
+        self
+    }
+
+    /// Makes `NgramData` split added texts into separate documents at
+    /// `boundary`. Boundary is stored with the data, so it can be checked
+    /// when the data is loaded from cache.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `self` isn't empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{Boundary, NgramData};
+    /// let mut corpus = NgramData::<2>::empty().with_boundary(Boundary::Newline);
+    /// corpus.add("end\nstart".chars());
+    /// assert_eq!(corpus.ngrams::<2>().get(&['d', 's']), None);
+    /// assert_eq!(corpus.ngrams::<2>().get(&['d', '\n']), None);
+    /// ```
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self {
+        assert!(
+            self.is_empty(),
+            "tried to change settings of counted ngrams"
+        );
+        self.boundary = boundary;
+        self
+    }
+
+    pub fn normalization(&self) -> &Normalization {
+        &self.normalization
+    }
+
+    pub fn boundary(&self) -> &Boundary {
+        &self.boundary
+    }
+
+    /// Checks if ends of documents are unknown, which happens when
+    /// `NgramData` is imported from a table of ngram counts. In that case
+    /// every ngram is treated as if it was followed by more text, so
+    /// shorter ngrams get counted only at the start of every ngram and
+    /// expansion doesn't know where documents start or end.
+    pub fn is_contraction_only(&self) -> bool {
+        self.contraction_only
+    }
+
+    /// Checks if no ngrams were counted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// assert!(NgramData::<2>::new("".chars()).is_empty());
+    /// assert!(!NgramData::<2>::new("a".chars()).is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.ngrams.is_empty() && self.tails.is_empty() && self.short.is_empty()
+    }
+
+    // Creates empty `NgramData` with the same settings as `self`.
+    fn empty_like<const M: usize>(&self) -> NgramData<M> {
+        let mut res = NgramData::empty()
+            .with_normalization(self.normalization)
+            .with_boundary(self.boundary.clone());
+        res.contraction_only = self.contraction_only;
+        res
+    }
+
+    /// Calculates `NgramData` from supplied iterator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let corpus = NgramData::<3>::new("Quick Fox".chars());
+    /// ```
+    pub fn new(iter: impl IntoIterator<Item = char>) -> Self {
+        Self::from_iter(iter)
+    }
+
+    /// Counts ngrams in supplied text. Every call is treated as a
+    /// separate document, so no ngrams are counted between texts. Texts
+    /// shorter than `N` are counted too, while empty ones are ignored.
+    /// Text gets normalized first and then split at boundaries.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let mut corpus = NgramData::<3>::empty();
+    /// corpus.add("Quick Fox".chars());
+    /// corpus.add("ok".chars());
+    /// assert_eq!(corpus.ngrams::<2>().get(&['o', 'k']), Some(&1));
+    /// ```
+    pub fn add(&mut self, iter: impl IntoIterator<Item = char>) {
+        match self.normalization.is_identity() {
+            true => self.count(iter),
+            false => self.count(self.normalization.apply(iter.into_iter())),
+        }
+    }
+
+    // Counts ngrams in text without normalizing it.
+    fn count(&mut self, iter: impl IntoIterator<Item = char>) {
+        let mut ngram = ['\0'; N];
+        let mut len = 0;
+        for token in Segments::new(iter.into_iter(), &self.boundary) {
+            match token {
+                Token::Char(char, _) if len < N => {
+                    ngram[len] = char;
+                    len += 1;
+                    if len == N {
+                        *self.heads.entry(ngram).or_insert(0) += 1;
+                    }
+                }
+                Token::Char(char, _) => {
+                    *self.ngrams.entry(ngram).or_insert(0) += 1;
+                    ngram.rotate_left(1);
+                    ngram[N - 1] = char;
+                }
+                Token::Break => {
+                    self.end_document(&ngram[..len]);
+                    len = 0;
+                }
+            }
+        }
+        self.end_document(&ngram[..len]);
+    }
+
+    // Counts the last ngram of a document.
+    fn end_document(&mut self, ngram: &[char]) {
+        match ngram.try_into() {
+            Ok(tail) => *self.tails.entry(tail).or_insert(0) += 1,
+            Err(_) if ngram.is_empty() => (),
+            Err(_) => *self.short.entry(ngram.to_vec()).or_insert(0) += 1,
+        }
+    }
+
+    /// Calculates `NgramData` from independent documents.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let messages = ["hi", "how are you?", "ok"];
+    /// let corpus = NgramData::<3>::from_documents(messages.map(str::chars));
+    /// assert_eq!(corpus.ngrams::<1>().get(&['o']), Some(&3));
+    /// ```
+    pub fn from_documents<I>(documents: impl IntoIterator<Item = I>) -> Self
+    where
+        I: IntoIterator<Item = char>,
+    {
+        let mut res = Self::empty();
+        res.add_documents(documents);
+        res
+    }
+
+    /// Counts ngrams in independent documents. Each of them has ngrams
+    /// counted as if it was added with [`NgramData::add`], so empty
+    /// documents don't change anything.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let mut corpus = NgramData::<3>::empty();
+    /// corpus.add_documents(["ab", "", "cd"].map(str::chars));
+    /// assert_eq!(corpus.ngrams::<2>().len(), 2);
+    /// ```
+    pub fn add_documents<I>(&mut self, documents: impl IntoIterator<Item = I>)
+    where
+        I: IntoIterator<Item = char>,
+    {
+        for document in documents {
+            self.add(document);
+        }
+    }
+
+    /// Adds ngrams counted in `other` to `self`. Texts counted in both
+    /// stay separate documents.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `self` and `other` use different
+    /// normalization or boundary.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let mut corpus = NgramData::<3>::new("Quick fox".chars());
+    /// corpus.merge(&NgramData::new("Lazy dog".chars()));
+    /// let expected = NgramData::from_documents(["Quick fox", "Lazy dog"].map(str::chars));
+    /// assert_eq!(corpus, expected);
+    /// ```
+    pub fn merge(&mut self, other: &Self) {
+        assert_eq!(
+            self.normalization, other.normalization,
+            "tried to merge ngrams with different normalization"
+        );
+        assert_eq!(
+            self.boundary, other.boundary,
+            "tried to merge ngrams with different boundary"
+        );
+        for (&ngram, count) in &other.ngrams {
+            *self.ngrams.entry(ngram).or_insert(0) += count;
+        }
+        for (&tail, count) in &other.tails {
+            *self.tails.entry(tail).or_insert(0) += count;
+        }
+        for (&head, count) in &other.heads {
+            *self.heads.entry(head).or_insert(0) += count;
+        }
+        for (short, count) in &other.short {
+            *self.short.entry(short.clone()).or_insert(0) += count;
+        }
+        self.contraction_only |= other.contraction_only;
+    }
+
+    // Counts ngrams of a whole text which occurred `count` times.
+    fn add_counted(&mut self, text: &[char], count: u64) {
+        if text.len() < N {
+            if !text.is_empty() {
+                *self.short.entry(text.to_vec()).or_insert(0) += count;
+            }
+            return;
+        }
+        let head = text[..N].try_into().unwrap();
+        *self.heads.entry(head).or_insert(0) += count;
+        let mut windows = text.windows(N).rev();
+        if let Some(tail) = windows.next() {
+            *self.tails.entry(tail.try_into().unwrap()).or_insert(0) += count;
+        }
+        for ngram in windows {
+            *self.ngrams.entry(ngram.try_into().unwrap()).or_insert(0) += count;
+        }
+    }
+
+    /// Creates `NgramData` from self where characters get expanded by `expansion`.
+    ///
+    /// # Panics
+    ///
+    /// The function *may* panic if `expansion` returns empty vec for any character.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let text = "Quick Fox";
+    /// let trigrams = NgramData::<3>::new(text.chars());
+    /// let trigrams = trigrams.expand(|char|
+    ///     if char.is_ascii_uppercase() {
+    ///         vec!['⇧', char.to_ascii_lowercase()]
+    ///     } else {
+    ///         vec![char]
+    ///     }
+    /// );
+    /// let trigrams = trigrams.ngrams();
+    /// assert_eq!(trigrams.get(&['Q', 'u', 'i']), None);
+    /// assert_eq!(trigrams.get(&['⇧', 'q', 'u']), Some(&1));
+    /// assert_eq!(trigrams.get(&[' ', '⇧', 'f']), Some(&1));
+    /// ```
+    pub fn expand<F, Iter>(&self, expand: F) -> Self
+    where
+        F: Fn(char) -> Iter,
+        Iter: IntoIterator<Item = char>,
+    {
+        let expand = &|char: &char| expand(*char);
+        let mut res = self.empty_like();
+        for (short, &count) in &self.short {
+            let expanded: Vec<_> = short.iter().flat_map(expand).collect();
+            res.add_counted(&expanded, count);
+        }
+        let Self {
+            ngrams,
+            tails,
+            heads,
+            ..
+        } = &mut res;
+        for (head, &count) in &self.heads {
+            let expanded = expand_full(head, expand).into_iter().next().unwrap();
+            *heads.entry(expanded).or_insert(0) += count;
+        }
+        for (tail, &count) in &self.tails {
+            let mut expanded = expand_full(tail, expand).into_iter().rev();
+            *tails.entry(expanded.next().unwrap()).or_insert(0) += count;
+            for ngram in expanded {
+                *ngrams.entry(ngram).or_insert(0) += count;
+            }
+        }
+        for (ngram, &count) in &self.ngrams {
+            for ngram in expand_first(ngram, expand) {
+                *ngrams.entry(ngram).or_insert(0) += count;
+            }
+        }
+        res
+    }
+
+    /// Creates `NgramData` from self where characters get expanded by
+    /// `expansion`, which can see their neighbours. Neighbours outside of
+    /// a document are `None`. Result is exact, but it can only hold
+    /// ngrams up to `N - 2` characters long, as the outermost characters
+    /// of stored ngrams only serve as context. Starts and ends of
+    /// documents have to be known, so it can't be used on
+    /// [contraction only](NgramData::is_contraction_only) data.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `M + 2 > N`, if `self` is contraction
+    /// only or if `expansion` returns an empty sequence.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{Context, NgramData};
+    /// // Capitals are typed with one-shot shift, while runs of them
+    /// // with caps lock.
+    /// let shift = |Context { prev, current, next }: Context<char>| {
+    ///     let upper = |char: Option<char>| char.is_some_and(|c| c.is_uppercase());
+    ///     let lower = current.to_ascii_lowercase();
+    ///     match (upper(prev), current.is_uppercase(), upper(next)) {
+    ///         (_, false, _) => vec![current],
+    ///         (false, true, false) => vec!['⇧', lower],
+    ///         (false, true, true) => vec!['⇪', lower],
+    ///         (true, true, false) => vec![lower, '⇪'],
+    ///         (true, true, true) => vec![lower],
+    ///     }
+    /// };
+    /// let corpus = NgramData::<4>::new("Hi ABC".chars());
+    /// let expanded = corpus.expand_with_context::<2, _, _>(shift);
+    /// let bigrams = expanded.ngrams::<2>();
+    /// assert_eq!(bigrams.get(&['⇧', 'h']), Some(&1));
+    /// assert_eq!(bigrams.get(&[' ', '⇪']), Some(&1));
+    /// assert_eq!(bigrams.get(&['c', '⇪']), Some(&1));
+    /// ```
+    pub fn expand_with_context<const M: usize, F, Iter>(&self, expand: F) -> NgramData<M>
+    where
+        F: Fn(Context<char>) -> Iter,
+        Iter: IntoIterator<Item = char>,
+    {
+        assert!(
+            M + 2 <= N,
+            "context expansion needs ngrams longer than {M} + 1"
+        );
+        assert!(
+            !self.contraction_only,
+            "context expansion needs known starts and ends of documents"
+        );
+        let expand = &expand;
+        let mut res = self.empty_like::<M>();
+        for (short, &count) in &self.short {
+            res.add_counted(&expand_document(short, expand), count);
+        }
+        // Every document is covered by its head, followed by windows
+        // starting in the second character of every ngram, followed
+        // by its tail.
+        for (head, &count) in &self.heads {
+            let expanded = expand_start(head, expand);
+            *res.heads.entry(expanded[0]).or_insert(0) += count;
+            for ngram in expanded {
+                *res.ngrams.entry(ngram).or_insert(0) += count;
+            }
+        }
+        for (ngram, &count) in &self.ngrams {
+            for ngram in expand_inner(ngram, expand) {
+                *res.ngrams.entry(ngram).or_insert(0) += count;
+            }
+        }
+        for (tail, &count) in &self.tails {
+            let mut expanded = expand_end(tail, expand).into_iter().rev();
+            *res.tails.entry(expanded.next().unwrap()).or_insert(0) += count;
+            for ngram in expanded {
+                *res.ngrams.entry(ngram).or_insert(0) += count;
+            }
+        }
+        res
+    }
+
+    /// Calculates the number of ngrams of length `K`
+    ///
+    /// # Panics
+    /// This function will panic if N < K.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let corpus = "Quick brown fox";
+    /// let trigrams = NgramData::<3>::new(corpus.chars());
+    /// let bigrams = NgramData::<2>::new(corpus.chars()).ngrams();
+    /// let contracted = trigrams.ngrams::<2>();
+    /// assert_eq!(bigrams, contracted);
+    /// ```
+    pub fn ngrams<const K: usize>(&self) -> Ngrams<K> {
+        assert!(K <= N);
+        self.tails
+            .iter()
+            .map(|(tail, count)| (&tail[..], count))
+            .chain(self.short.iter().map(|(short, count)| (&short[..], count)))
+            .flat_map(|(text, count)| {
+                text.windows(K)
+                    .map(move |window| (window.try_into().unwrap(), count))
+            })
+            .chain(
+                self.ngrams
+                    .iter()
+                    .map(|(ngram, count)| (ngram[..K].try_into().unwrap(), count)),
+            )
+            .fold(Ngrams::new(), |mut ngrams, (ngram, &count)| {
+                *ngrams.entry(ngram).or_insert(0) += count;
+                ngrams
+            })
+    }
+
+    // Like `ngrams`, but for length known at runtime.
+    pub(super) fn contract(&self, len: usize) -> HashMap<Vec<char>, u64> {
+        assert!(len <= N);
+        let mut res = HashMap::new();
+        let texts = self.tails.iter().map(|(tail, count)| (&tail[..], count));
+        for (text, count) in texts.chain(self.short.iter().map(|(s, count)| (&s[..], count))) {
+            for window in text.windows(len) {
+                *res.entry(window.to_vec()).or_insert(0) += count;
+            }
+        }
+        for (ngram, count) in &self.ngrams {
+            *res.entry(ngram[..len].to_vec()).or_insert(0) += count;
+        }
+        res
+    }
+
+    /// Calculates the number of skipgrams, pairs of characters with `gap`
+    /// characters between them. Gap of 0 gives bigrams.
+    ///
+    /// # Panics
+    /// This function will panic if N < gap + 2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let trigrams = NgramData::<3>::new("abcb".chars());
+    /// let skipgrams = trigrams.skipgrams(1);
+    /// assert_eq!(skipgrams.get(&['a', 'c']), Some(&1));
+    /// assert_eq!(skipgrams.get(&['b', 'b']), Some(&1));
+    /// assert_eq!(skipgrams.len(), 2);
+    /// ```
+    pub fn skipgrams(&self, gap: usize) -> Ngrams<2> {
+        assert!(gap + 2 <= N);
+        let len = gap + 2;
+        self.tails
+            .iter()
+            .map(|(tail, count)| (&tail[..], count))
+            .chain(self.short.iter().map(|(short, count)| (&short[..], count)))
+            .flat_map(|(text, count)| {
+                text.windows(len)
+                    .map(move |window| ([window[0], window[len - 1]], count))
+            })
+            .chain(
+                self.ngrams
+                    .iter()
+                    .map(|(ngram, count)| ([ngram[0], ngram[len - 1]], count)),
+            )
+            .fold(Ngrams::new(), |mut skipgrams, (skipgram, &count)| {
+                *skipgrams.entry(skipgram).or_insert(0) += count;
+                skipgrams
+            })
+    }
+
+    /// Calculates skipgrams for every gap from 1 up to N - 2. Table with
+    /// index `i` contains skipgrams with gap `i + 1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let corpus = NgramData::<4>::new("abcd".chars());
+    /// let skipgrams = corpus.skipgram_tables();
+    /// assert_eq!(skipgrams.len(), 2);
+    /// assert_eq!(skipgrams[1].get(&['a', 'd']), Some(&1));
+    /// ```
+    pub fn skipgram_tables(&self) -> Vec<Ngrams<2>> {
+        (1..N.saturating_sub(1))
+            .map(|gap| self.skipgrams(gap))
+            .collect()
+    }
+}
+
+impl<const N: usize> FromIterator<char> for NgramData<N> {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut res = Self::empty();
+        res.add(iter);
+        res
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IngestError {
+    Io(IoError),
+    /// Invalid UTF-8 was found at given byte offset.
+    InvalidUtf8(u64),
+}
+
+impl From<io::Error> for IngestError {
+    fn from(error: io::Error) -> Self {
+        IngestError::Io(IoError(Arc::new(error)))
+    }
+}
+
+impl Display for IngestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use IngestError::*;
+        match self {
+            Io(error) => write!(f, "failed to read text: {error}"),
+            InvalidUtf8(offset) => write!(f, "invalid UTF-8 at byte {offset}"),
+        }
+    }
+}
+
+impl std::error::Error for IngestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IngestError::Io(error) => Some(error.get()),
+            _ => None,
+        }
+    }
+}
+
+/// Shared [`io::Error`], so errors containing it can be cloned and
+/// compared. Errors are equal if they have the same kind and message.
+#[derive(Clone, Debug)]
+pub struct IoError(Arc<io::Error>);
+
+impl IoError {
+    pub fn get(&self) -> &io::Error {
+        &self.0
+    }
+
+    pub fn kind(&self) -> io::ErrorKind {
+        self.0.kind()
+    }
+}
+
+impl PartialEq for IoError {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind() == other.kind() && self.0.to_string() == other.0.to_string()
+    }
+}
+
+impl Eq for IoError {}
+
+impl Display for IoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn trigrams() {
+        let text = "Aaaaa";
+        let expected: Ngrams<3> = [(['A', 'a', 'a'], 1), (['a', 'a', 'a'], 2)]
+            .into_iter()
+            .collect();
+        let trigrams: NgramData<3> = NgramData::new(text.chars());
+        let trigrams = trigrams.ngrams::<3>();
+        assert_eq!(trigrams, expected);
+    }
+
+    #[test]
+    pub fn shorter_ngrams() {
+        let text = "Quick fox";
+        let expected: Ngrams<2> = NgramData::<2>::new(text.chars()).ngrams();
+        let trigrams = NgramData::<3>::new(text.chars());
+        let bigrams = trigrams.ngrams();
+        assert_eq!(bigrams, expected);
+    }
+
+    #[test]
+    pub fn short_documents() {
+        let documents = ["Qu", "ick", "fox", "a"];
+        let mut expected = Ngrams::<2>::new();
+        for document in documents {
+            for (ngram, count) in NgramData::<2>::new(document.chars()).ngrams() {
+                *expected.entry(ngram).or_insert(0) += count;
+            }
+        }
+        let ngrams = NgramData::<4>::from_documents(documents.map(str::chars));
+        assert_eq!(ngrams.ngrams::<2>(), expected);
+        assert_eq!(ngrams.ngrams::<1>().values().sum::<u64>(), 9);
+    }
+
+    #[test]
+    pub fn short_expansion() {
+        let expansion = |char| vec!['.', char];
+        let ngrams = NgramData::<3>::new("ab".chars()).expand(expansion);
+        let expected = NgramData::<3>::new("ab".chars().flat_map(expansion));
+        assert_eq!(ngrams, expected);
+    }
+
+    #[test]
+    pub fn skipgrams() {
+        let text = "Quick brown fox";
+        let chars: Vec<_> = text.chars().collect();
+        let mut expected = Ngrams::<2>::new();
+        for window in chars.windows(4) {
+            *expected.entry([window[0], window[3]]).or_insert(0) += 1;
+        }
+        let skipgrams = NgramData::<5>::new(text.chars()).skipgrams(2);
+        assert_eq!(skipgrams, expected);
+    }
+
+    #[test]
+    pub fn context_expansion() {
+        let repeat = |context: Context<char>| match context.prev == Some(context.current) {
+            true => vec!['@'],
+            false => vec![context.current],
+        };
+        let documents = ["aaab", "abba", "bb", "a", "aabbaabbbb"];
+        let corpus = NgramData::<5>::from_documents(documents.map(str::chars));
+        let expanded = documents.map(|document| {
+            let chars: Vec<_> = document.chars().collect();
+            expand_document(&chars, repeat)
+        });
+        let expected = NgramData::<3>::from_documents(expanded.clone());
+        assert_eq!(corpus.expand_with_context(repeat), expected);
+        let expected = NgramData::<2>::from_documents(expanded);
+        assert_eq!(corpus.expand_with_context(repeat), expected);
+        // Without heads first windows of documents would be lost.
+        let table = "aaab\t1\n";
+        let imported = NgramData::<4>::read_table(table.as_bytes(), TableFormat::Tsv).unwrap();
+        assert!(imported.is_contraction_only());
+        let result = std::panic::catch_unwind(|| imported.expand_with_context::<2, _, _>(repeat));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    pub fn no_expansion() {
+        let text = "quick fox";
+        let before = NgramData::<3>::new(text.chars());
+        let after = before.expand(|char| vec![char]);
+        assert_eq!(after, before);
+    }
+
+    #[test]
+    pub fn simple_expansion() {
+        let text = "Quick fox";
+        let expansion = |char| vec!['.', char];
+        let ngrams = NgramData::<2>::new(text.chars()).expand(expansion).ngrams();
+        let expected = NgramData::<3>::new(text.chars().flat_map(expansion)).ngrams::<2>();
+        assert_eq!(ngrams, expected);
+    }
+}
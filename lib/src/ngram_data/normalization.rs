@@ -0,0 +1,347 @@
+use std::collections::VecDeque;
+use std::iter::Peekable;
+use unicode_normalization::char::{canonical_combining_class, compose, decompose_canonical};
+use unicode_normalization::UnicodeNormalization;
+
+/// Unicode normalization form.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnicodeForm {
+    /// Canonical composition, so `e` followed by combining acute accent
+    /// becomes `é`.
+    Nfc,
+    /// Canonical decomposition, so `é` becomes `e` followed by combining
+    /// acute accent.
+    Nfd,
+}
+
+/// Text cleaning applied before ngrams get counted. Default one leaves
+/// text unchanged. Steps are applied in the order of fields.
+///
+/// # Example
+///
+/// ```
+/// use algae_lib::{Normalization, UnicodeForm};
+/// let normalization = Normalization {
+///     line_endings: true,
+///     lowercase: true,
+///     form: Some(UnicodeForm::Nfc),
+///     ..Normalization::default()
+/// };
+/// let text: String = normalization.apply("Cafe\u{301}\r\n".chars()).collect();
+/// assert_eq!(text, "café\n");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Normalization {
+    /// Replace `\r\n` and lone `\r` with `\n`.
+    pub line_endings: bool,
+    /// Replace characters with their lowercase forms. This isn't full
+    /// Unicode case folding, e.g. `ß` stays unchanged and `Σ` always
+    /// becomes `σ`, see [`Normalization::case_fold`] for that.
+    pub lowercase: bool,
+    /// Replace characters with their full Unicode case folding, so
+    /// that `STRASSE` and `straße` become the same text. Folded text is
+    /// lowercase, except Cherokee which folds to uppercase. Folding that
+    /// only decomposes a character is left out, so results are
+    /// canonically equivalent to `CaseFolding.txt` ones.
+    pub case_fold: bool,
+    pub form: Option<UnicodeForm>,
+    /// Replace typographic quotes and dashes with their ASCII
+    /// counterparts.
+    pub ascii_punctuation: bool,
+    /// Remove control characters other than newlines and tabs.
+    pub drop_control: bool,
+    /// Replace every run of whitespace other than newlines with a single
+    /// space. This includes non-breaking spaces.
+    pub collapse_whitespace: bool,
+}
+
+impl Normalization {
+    /// Applies normalization to text.
+    pub fn apply<'a>(
+        &self,
+        text: impl Iterator<Item = char> + 'a,
+    ) -> Box<dyn Iterator<Item = char> + 'a> {
+        Box::new(
+            self.apply_tagged(text.map(|char| (char, ())))
+                .map(|(char, _)| char),
+        )
+    }
+
+    // Applies normalization to chars tagged with values, e.g. times they
+    // were typed at. Chars made of several others get the tag of the last
+    // one.
+    pub(super) fn apply_tagged<'a, T: Clone + 'a>(
+        &self,
+        text: impl Iterator<Item = (char, T)> + 'a,
+    ) -> Box<dyn Iterator<Item = (char, T)> + 'a> {
+        let mut text: Box<dyn Iterator<Item = (char, T)>> = Box::new(text);
+        if self.line_endings {
+            text = Box::new(
+                text.scan(false, |after_cr, (char, tag)| {
+                    let skip = *after_cr && char == '\n';
+                    *after_cr = char == '\r';
+                    Some((!skip).then_some((if char == '\r' { '\n' } else { char }, tag)))
+                })
+                .flatten(),
+            );
+        }
+        if self.lowercase || self.case_fold {
+            text =
+                Box::new(text.flat_map(|(char, tag)| {
+                    char.to_lowercase().map(move |char| (char, tag.clone()))
+                }));
+        }
+        if self.case_fold {
+            text = Box::new(
+                text.flat_map(|(char, tag)| case_fold(char).map(move |char| (char, tag.clone()))),
+            );
+        }
+        if let Some(form) = self.form {
+            text = Box::new(Forms::new(text, form));
+        }
+        if self.ascii_punctuation {
+            text = Box::new(text.map(|(char, tag)| (ascii_punctuation(char), tag)));
+        }
+        if self.drop_control {
+            text = Box::new(
+                text.filter(|&(char, _)| !char.is_control() || char == '\n' || char == '\t'),
+            );
+        }
+        if self.collapse_whitespace {
+            text = Box::new(
+                text.scan(false, |after_space, (char, tag)| {
+                    let space = char.is_whitespace() && char != '\n';
+                    let skip = *after_space && space;
+                    *after_space = space;
+                    Some((!skip).then_some((if space { ' ' } else { char }, tag)))
+                })
+                .flatten(),
+            );
+        }
+        text
+    }
+
+    pub(super) fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+// Converts text to a Unicode form one run of chars at a time. Runs end
+// before chars that can't combine with the previous one, so the result
+// is the same as for the whole text.
+struct Forms<I: Iterator, T> {
+    text: Peekable<I>,
+    form: UnicodeForm,
+    run: String,
+    normalized: VecDeque<(char, T)>,
+}
+
+impl<I: Iterator<Item = (char, T)>, T: Clone> Forms<I, T> {
+    fn new(text: I, form: UnicodeForm) -> Self {
+        Self {
+            text: text.peekable(),
+            form,
+            run: String::new(),
+            normalized: VecDeque::new(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = (char, T)>, T: Clone> Iterator for Forms<I, T> {
+    type Item = (char, T);
+
+    fn next(&mut self) -> Option<(char, T)> {
+        if let Some(next) = self.normalized.pop_front() {
+            return Some(next);
+        }
+        let (first, mut tag) = self.text.next()?;
+        self.run.clear();
+        self.run.push(first);
+        // Starter that following chars may get composed with.
+        let mut starter = first;
+        while let Some(&(char, _)) = self.text.peek() {
+            let composed = match self.form {
+                UnicodeForm::Nfc => compose(starter, char),
+                UnicodeForm::Nfd => None,
+            };
+            if composed.is_none() && canonical_combining_class(char) == 0 {
+                break;
+            }
+            starter = composed.unwrap_or(starter);
+            let (char, next_tag) = self.text.next().unwrap();
+            self.run.push(char);
+            tag = next_tag;
+        }
+        let run: Box<dyn Iterator<Item = char>> = match self.form {
+            UnicodeForm::Nfc => Box::new(self.run.chars().nfc()),
+            UnicodeForm::Nfd => Box::new(self.run.chars().nfd()),
+        };
+        self.normalized.extend(run.map(|char| (char, tag.clone())));
+        self.normalized.pop_front()
+    }
+}
+
+// Folds a lowercase char. Only chars whose folding differs from their
+// lowercase form up to canonical equivalence are changed.
+fn case_fold(char: char) -> impl Iterator<Item = char> {
+    let mut folded = ['\0'; 4];
+    let len = match char {
+        'ß' => fold(&mut folded, "ss"),
+        'ς' => fold(&mut folded, "σ"),
+        'ſ' => fold(&mut folded, "s"),
+        'µ' => fold(&mut folded, "μ"),
+        'ŉ' => fold(&mut folded, "ʼn"),
+        'ẚ' => fold(&mut folded, "aʾ"),
+        'ẛ' => fold(&mut folded, "ṡ"),
+        'ϐ' => fold(&mut folded, "β"),
+        'ϑ' => fold(&mut folded, "θ"),
+        'ϕ' => fold(&mut folded, "φ"),
+        'ϖ' => fold(&mut folded, "π"),
+        'ϰ' => fold(&mut folded, "κ"),
+        'ϱ' => fold(&mut folded, "ρ"),
+        'ϵ' => fold(&mut folded, "ε"),
+        '\u{345}' | '\u{1fbe}' => fold(&mut folded, "ι"),
+        'ﬀ' => fold(&mut folded, "ff"),
+        'ﬁ' => fold(&mut folded, "fi"),
+        'ﬂ' => fold(&mut folded, "fl"),
+        'ﬃ' => fold(&mut folded, "ffi"),
+        'ﬄ' => fold(&mut folded, "ffl"),
+        'ﬅ' | 'ﬆ' => fold(&mut folded, "st"),
+        'և' => fold(&mut folded, "եւ"),
+        'ﬓ' => fold(&mut folded, "մն"),
+        'ﬔ' => fold(&mut folded, "մե"),
+        'ﬕ' => fold(&mut folded, "մի"),
+        'ﬖ' => fold(&mut folded, "վն"),
+        'ﬗ' => fold(&mut folded, "մխ"),
+        'ᲀ' => fold(&mut folded, "в"),
+        'ᲁ' => fold(&mut folded, "д"),
+        'ᲂ' => fold(&mut folded, "о"),
+        'ᲃ' => fold(&mut folded, "с"),
+        'ᲄ' | 'ᲅ' => fold(&mut folded, "т"),
+        'ᲆ' => fold(&mut folded, "ъ"),
+        'ᲇ' => fold(&mut folded, "ѣ"),
+        'ᲈ' => fold(&mut folded, "ꙋ"),
+        // Cherokee folds to uppercase.
+        '\u{13f8}'..='\u{13fd}' => {
+            folded[0] = char::from_u32(char as u32 - 8).unwrap();
+            1
+        }
+        '\u{ab70}'..='\u{abbf}' => {
+            folded[0] = char::from_u32(char as u32 - 0xab70 + 0x13a0).unwrap();
+            1
+        }
+        // Greek letters with iota subscript fold to ones followed by iota.
+        '\u{1f80}'..='\u{1fff}' => {
+            let mut len = 0;
+            decompose_canonical(char, |char| {
+                folded[len] = if char == '\u{345}' { 'ι' } else { char };
+                len += 1;
+            });
+            match folded[..len].contains(&'ι') {
+                true => len,
+                false => {
+                    folded[0] = char;
+                    1
+                }
+            }
+        }
+        char => {
+            folded[0] = char;
+            1
+        }
+    };
+    folded.into_iter().take(len)
+}
+
+fn fold(folded: &mut [char; 4], str: &str) -> usize {
+    let mut len = 0;
+    for char in str.chars() {
+        folded[len] = char;
+        len += 1;
+    }
+    len
+}
+
+fn ascii_punctuation(char: char) -> char {
+    match char {
+        '‘' | '’' | '‚' | '‛' | '′' => '\'',
+        '“' | '”' | '„' | '‟' | '″' | '«' | '»' => '"',
+        '‐' | '‑' | '‒' | '–' | '—' | '―' | '−' => '-',
+        char => char,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(normalization: Normalization, text: &str) -> String {
+        normalization.apply(text.chars()).collect()
+    }
+
+    #[test]
+    pub fn line_endings() {
+        let normalization = Normalization {
+            line_endings: true,
+            ..Normalization::default()
+        };
+        assert_eq!(normalize(normalization, "a\r\nb\rc\n\r\n"), "a\nb\nc\n\n");
+    }
+
+    #[test]
+    pub fn punctuation_and_whitespace() {
+        let normalization = Normalization {
+            ascii_punctuation: true,
+            drop_control: true,
+            collapse_whitespace: true,
+            ..Normalization::default()
+        };
+        let text = "“Quick”\u{7}\u{a0} \t–\n\n fox’s";
+        assert_eq!(normalize(normalization, text), "\"Quick\" -\n\n fox's");
+    }
+
+    #[test]
+    pub fn lowercase() {
+        let normalization = Normalization {
+            lowercase: true,
+            ..Normalization::default()
+        };
+        assert_eq!(normalize(normalization, "ÉTÉ İ"), "été i\u{307}");
+        assert_eq!(normalize(normalization, "STRAẞE ΟΔΟΣ"), "straße οδοσ");
+    }
+
+    #[test]
+    pub fn case_fold() {
+        let normalization = Normalization {
+            case_fold: true,
+            form: Some(UnicodeForm::Nfc),
+            ..Normalization::default()
+        };
+        let fold = |text| normalize(normalization, text);
+        assert_eq!(fold("STRASSE"), fold("straße"));
+        assert_eq!(fold("STRAẞE"), "strasse");
+        assert_eq!(fold("ΟΔΟΣ"), fold("οδος"));
+        assert_eq!(fold("ﬁle ꭰ ᾼ"), "file Ꭰ αι");
+        assert_eq!(fold("ᾀ"), "ἀι");
+        assert_eq!(fold("ǰ É"), "ǰ é");
+    }
+
+    #[test]
+    pub fn unicode_forms() {
+        let nfd = Normalization {
+            form: Some(UnicodeForm::Nfd),
+            ..Normalization::default()
+        };
+        assert_eq!(normalize(nfd, "é"), "e\u{301}");
+        let nfc = Normalization {
+            form: Some(UnicodeForm::Nfc),
+            ..Normalization::default()
+        };
+        assert_eq!(normalize(nfc, "e\u{301}"), "é");
+        // Runs of composed chars can start with several starters.
+        let text = "\u{1100}\u{1161}\u{11a8} e\u{323}\u{302}";
+        assert_eq!(normalize(nfc, text), text.nfc().collect::<String>());
+        let tagged: Vec<_> = nfc.apply_tagged("e\u{301}x".chars().zip(0..)).collect();
+        assert_eq!(tagged, [('é', 1), ('x', 2)]);
+    }
+}
@@ -0,0 +1,177 @@
+use super::boundary::{Segments, Token};
+use super::{Boundary, NgramData};
+use std::borrow::Cow;
+use std::thread;
+
+impl<const N: usize> NgramData<N> {
+    /// Calculates `NgramData` from `text` using up to `threads` threads.
+    /// Result is the same as the one of [`NgramData::new`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let text = "Quick brown fox jumps over a lazy dog";
+    /// let corpus = NgramData::<3>::new_parallel(text, 4);
+    /// assert_eq!(corpus, NgramData::new(text.chars()));
+    /// ```
+    pub fn new_parallel(text: &str, threads: usize) -> Self {
+        let mut res = Self::empty();
+        res.add_parallel(text, threads);
+        res
+    }
+
+    /// Counts ngrams in `text` as a single document using up to `threads`
+    /// threads. Text is split into chunks which are counted separately
+    /// and merged afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let mut corpus = NgramData::<3>::empty();
+    /// corpus.add_parallel("Quick brown fox", 2);
+    /// assert_eq!(corpus, NgramData::new("Quick brown fox".chars()));
+    /// ```
+    pub fn add_parallel(&mut self, text: &str, threads: usize) {
+        // Normalization depends on neighbouring characters, so it can't
+        // be done on chunks.
+        let text = match self.normalization.is_identity() {
+            true => Cow::Borrowed(text),
+            false => Cow::Owned(self.normalization.apply(text.chars()).collect()),
+        };
+        let threads = threads.max(1);
+        let mut empty = self.empty_like();
+        empty.boundary = Boundary::None;
+        let counted = match self.boundary {
+            Boundary::None => {
+                let chunks = chunks::<N>(&text, threads);
+                let last = chunks.len() - 1;
+                spawn_all(chunks, |i, chunk| {
+                    let mut res = empty.clone();
+                    res.count(chunk.chars());
+                    // Only the first chunk starts the text and only the
+                    // last one ends it. Tails of the others are followed
+                    // by more characters.
+                    if i != 0 {
+                        res.heads.clear();
+                    }
+                    if i != last {
+                        for (tail, count) in std::mem::take(&mut res.tails) {
+                            *res.ngrams.entry(tail).or_insert(0) += count;
+                        }
+                    }
+                    res
+                })
+            }
+            // Boundaries can't be found in chunks either, so instead
+            // documents are found first and counted in groups.
+            _ => {
+                let mut documents = vec![String::new()];
+                for token in Segments::new(text.chars(), &self.boundary) {
+                    match token {
+                        Token::Char(char, _) => documents.last_mut().unwrap().push(char),
+                        Token::Break => documents.push(String::new()),
+                    }
+                }
+                let groups: Vec<_> = documents
+                    .chunks(documents.len().div_ceil(threads))
+                    .collect();
+                spawn_all(groups, |_, group| {
+                    let mut res = empty.clone();
+                    for document in group {
+                        res.count(document.chars());
+                    }
+                    res
+                })
+            }
+        };
+        // Chunks were counted without boundaries, which are already
+        // handled.
+        for mut chunk in counted {
+            chunk.boundary = self.boundary.clone();
+            self.merge(&chunk);
+        }
+    }
+}
+
+// Calls `f` on every item in a separate thread.
+fn spawn_all<T, R, F>(items: Vec<T>, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(usize, T) -> R + Sync,
+{
+    thread::scope(|scope| {
+        let f = &f;
+        let handles: Vec<_> = items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| scope.spawn(move || f(i, item)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+// Splits text into overlapping chunks. Every chunk except the last one
+// contains `N - 1` characters of the following chunk, so windows starting
+// in a chunk are counted by exactly one chunk. Every chunk except the
+// last one is followed by at least `N` characters.
+fn chunks<const N: usize>(text: &str, count: usize) -> Vec<&str> {
+    let mut chunks = Vec::with_capacity(count);
+    let mut start = 0;
+    for i in 1..count {
+        let mut end = (text.len() * i / count).max(start);
+        while !text.is_char_boundary(end) {
+            end += 1;
+        }
+        if end == start {
+            continue;
+        }
+        match text[end..].char_indices().nth(N - 1) {
+            Some((overlap, _)) => {
+                chunks.push(&text[start..end + overlap]);
+                start = end;
+            }
+            None => break,
+        }
+    }
+    chunks.push(&text[start..]);
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn same_as_sequential() {
+        let text = "Zażółć gęślą jaźń, quick brown fox";
+        for threads in 1..40 {
+            let expected = NgramData::<4>::new(text.chars());
+            assert_eq!(NgramData::<4>::new_parallel(text, threads), expected);
+            let expected = NgramData::<1>::new(text.chars());
+            assert_eq!(NgramData::<1>::new_parallel(text, threads), expected);
+        }
+        let short = "ab";
+        let expected = NgramData::<3>::new(short.chars());
+        assert_eq!(NgramData::<3>::new_parallel(short, 8), expected);
+    }
+
+    #[test]
+    pub fn boundaries() {
+        let text = "a\nquick\n\nbrown fox\n";
+        for boundary in [Boundary::Newline, Boundary::BlankLine] {
+            let mut expected = NgramData::<3>::empty().with_boundary(boundary.clone());
+            expected.add(text.chars());
+            for threads in 1..8 {
+                let mut corpus = NgramData::<3>::empty().with_boundary(boundary.clone());
+                corpus.add_parallel(text, threads);
+                assert_eq!(corpus, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use super::NgramData;
+use std::collections::{HashMap, HashSet};
+use std::ops::Add;
+
+/// Number of counted ngrams removed by pruning. Texts shorter than `N`
+/// count as single ngrams.
+///
+/// Only ngrams of length `N` are counted. The last ngram of a document
+/// also holds shorter ngrams at its end, which are removed together
+/// with it without being counted in `removed`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pruned {
+    pub removed: u64,
+    /// Number of ngrams considered by the pruning. Texts shorter than
+    /// `N` are included only if the pruning can remove them.
+    pub total: u64,
+}
+
+impl Pruned {
+    /// Fraction of ngrams that got removed.
+    pub fn fraction(&self) -> f64 {
+        match self.total {
+            0 => 0.0,
+            total => self.removed as f64 / total as f64,
+        }
+    }
+}
+
+impl<const N: usize> NgramData<N> {
+    /// Removes ngrams that occurred less than `min` times.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let mut corpus = NgramData::<2>::new("aaab".chars());
+    /// let pruned = corpus.prune_min_count(2);
+    /// assert_eq!((pruned.removed, pruned.total), (1, 3));
+    /// assert_eq!(corpus.ngrams::<2>().get(&['a', 'b']), None);
+    /// ```
+    pub fn prune_min_count(&mut self, min: u64) -> Pruned {
+        let counts = self.counts();
+        let pruned = self.retain(|ngram| counts[ngram] >= min);
+        pruned + self.retain_short(|_, count| count >= min)
+    }
+
+    /// Keeps only `k` most frequent ngrams. Texts shorter than `N` are
+    /// left untouched.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let mut corpus = NgramData::<2>::new("aaab".chars());
+    /// corpus.prune_top_k(1);
+    /// assert_eq!(corpus.ngrams::<2>().len(), 1);
+    /// ```
+    pub fn prune_top_k(&mut self, k: usize) -> Pruned {
+        let kept: HashSet<_> = self.sorted_counts().into_iter().take(k).collect();
+        self.retain(|ngram| kept.contains(ngram))
+    }
+
+    /// Keeps most frequent ngrams that together make up at least
+    /// `threshold` fraction of all ngrams. Texts shorter than `N` are
+    /// left untouched.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let mut corpus = NgramData::<2>::new("aaaaab".chars());
+    /// let pruned = corpus.prune_cumulative(0.75);
+    /// assert_eq!(pruned.fraction(), 0.2);
+    /// ```
+    pub fn prune_cumulative(&mut self, threshold: f64) -> Pruned {
+        let counts = self.counts();
+        let total: u64 = counts.values().sum();
+        let mut cumulative = 0;
+        let kept: HashSet<_> = self
+            .sorted_counts()
+            .into_iter()
+            .take_while(|ngram| {
+                let done = cumulative as f64 >= threshold * total as f64;
+                cumulative += counts[ngram];
+                !done
+            })
+            .collect();
+        self.retain(|ngram| kept.contains(ngram))
+    }
+
+    /// Removes ngrams containing characters outside of `charset`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let mut corpus = NgramData::<2>::new("ab€".chars());
+    /// let pruned = corpus.prune_charset('a'..='z');
+    /// assert_eq!(pruned.fraction(), 0.5);
+    /// ```
+    pub fn prune_charset(&mut self, charset: impl IntoIterator<Item = char>) -> Pruned {
+        let charset: HashSet<_> = charset.into_iter().collect();
+        let pruned = self.retain(|ngram| ngram.iter().all(|c| charset.contains(c)));
+        pruned + self.retain_short(|short, _| short.iter().all(|c| charset.contains(c)))
+    }
+
+    // Counts of ngrams including tails.
+    fn counts(&self) -> HashMap<[char; N], u64> {
+        let mut counts = self.ngrams.clone();
+        for (&tail, count) in &self.tails {
+            *counts.entry(tail).or_insert(0) += count;
+        }
+        counts
+    }
+
+    // Ngrams sorted from the most frequent.
+    fn sorted_counts(&self) -> Vec<[char; N]> {
+        let mut counts: Vec<_> = self.counts().into_iter().collect();
+        counts.sort_unstable_by(|(n1, c1), (n2, c2)| c2.cmp(c1).then(n1.cmp(n2)));
+        counts.into_iter().map(|(ngram, _)| ngram).collect()
+    }
+
+    fn retain(&mut self, mut keep: impl FnMut(&[char; N]) -> bool) -> Pruned {
+        let mut pruned = Pruned {
+            removed: 0,
+            total: 0,
+        };
+        for counts in [&mut self.ngrams, &mut self.tails] {
+            counts.retain(|ngram, count| {
+                pruned.total += *count;
+                let keep = keep(ngram);
+                if !keep {
+                    pruned.removed += *count;
+                }
+                keep
+            });
+        }
+        // Heads are also counted as ngrams or tails.
+        self.heads.retain(|head, _| keep(head));
+        pruned
+    }
+
+    fn retain_short(&mut self, mut keep: impl FnMut(&[char], u64) -> bool) -> Pruned {
+        let mut pruned = Pruned {
+            removed: 0,
+            total: 0,
+        };
+        self.short.retain(|short, count| {
+            pruned.total += *count;
+            let keep = keep(short, *count);
+            if !keep {
+                pruned.removed += *count;
+            }
+            keep
+        });
+        pruned
+    }
+}
+
+impl Add for Pruned {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            removed: self.removed + rhs.removed,
+            total: self.total + rhs.total,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn short_texts() {
+        let documents = ["aaab", "ab", "b", "b", "€"];
+        let corpus = NgramData::<3>::from_documents(documents.map(str::chars));
+        let pruned = corpus.clone().prune_min_count(2);
+        assert_eq!((pruned.removed, pruned.total), (4, 6));
+        // Short texts aren't pruned by frequency of ngrams.
+        let mut top = corpus.clone();
+        let pruned = top.prune_top_k(1);
+        assert_eq!((pruned.removed, pruned.total), (1, 2));
+        assert_eq!(top.ngrams::<1>().get(&['b']), Some(&3));
+        let mut cumulative = corpus.clone();
+        assert_eq!(cumulative.prune_cumulative(1.0).removed, 0);
+        assert_eq!(cumulative, corpus);
+        let mut charset = corpus;
+        let pruned = charset.prune_charset(['a', 'b']);
+        assert_eq!((pruned.removed, pruned.total), (1, 6));
+        assert_eq!(charset.ngrams::<1>().get(&['€']), None);
+    }
+}
@@ -0,0 +1,248 @@
+use super::{IngestError, NgramData};
+use std::io::{ErrorKind, Read};
+
+const CHUNK_SIZE: usize = 1 << 16;
+
+/// What to do with bytes that aren't valid UTF-8.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InvalidBytes {
+    /// Stop reading and return an error.
+    #[default]
+    Error,
+    /// Replace them with `U+FFFD REPLACEMENT CHARACTER`.
+    Replace,
+    /// Ignore them.
+    Skip,
+}
+
+impl<const N: usize> NgramData<N> {
+    /// Calculates `NgramData` from text read from `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails or if invalid UTF-8 is
+    /// encountered with [`InvalidBytes::Error`] policy.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{InvalidBytes, NgramData};
+    /// let text = "Quick fox";
+    /// let corpus = NgramData::<3>::from_reader(text.as_bytes(), InvalidBytes::Error).unwrap();
+    /// assert_eq!(corpus, NgramData::new(text.chars()));
+    /// ```
+    pub fn from_reader(reader: impl Read, invalid: InvalidBytes) -> Result<Self, IngestError> {
+        let mut res = Self::empty();
+        res.add_reader(reader, invalid)?;
+        Ok(res)
+    }
+
+    /// Counts ngrams in text read from `reader` as a single document.
+    /// Text is read and decoded in chunks, so it doesn't have to fit in
+    /// memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails or if invalid UTF-8 is
+    /// encountered with [`InvalidBytes::Error`] policy. In that case
+    /// `self` is left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{InvalidBytes, NgramData};
+    /// let bytes = b"ab\xffc";
+    /// let mut corpus = NgramData::<2>::empty();
+    /// assert!(corpus.add_reader(&bytes[..], InvalidBytes::Error).is_err());
+    /// corpus.add_reader(&bytes[..], InvalidBytes::Skip).unwrap();
+    /// assert_eq!(corpus, NgramData::new("abc".chars()));
+    /// ```
+    pub fn add_reader(
+        &mut self,
+        reader: impl Read,
+        invalid: InvalidBytes,
+    ) -> Result<(), IngestError> {
+        let mut chars = Chars::new(reader, invalid);
+        let mut res = self.empty_like();
+        res.add(&mut chars);
+        match chars.error {
+            Some(error) => Err(error),
+            None => {
+                self.merge(&res);
+                Ok(())
+            }
+        }
+    }
+}
+
+// Decodes chars from a reader. Iteration stops on the first error, which
+// is then stored in `error`.
+struct Chars<R> {
+    reader: R,
+    invalid: InvalidBytes,
+    buffer: Box<[u8]>,
+    start: usize,
+    end: usize,
+    // Number of bytes consumed before `buffer[start]`.
+    offset: u64,
+    eof: bool,
+    error: Option<IngestError>,
+}
+
+enum Decoded {
+    Char(char, usize),
+    Invalid(usize),
+    Incomplete,
+}
+
+impl<R: Read> Chars<R> {
+    fn new(reader: R, invalid: InvalidBytes) -> Self {
+        Self {
+            reader,
+            invalid,
+            buffer: vec![0; CHUNK_SIZE].into_boxed_slice(),
+            start: 0,
+            end: 0,
+            offset: 0,
+            eof: false,
+            error: None,
+        }
+    }
+
+    fn refill(&mut self) {
+        self.buffer.copy_within(self.start..self.end, 0);
+        self.end -= self.start;
+        self.start = 0;
+        loop {
+            match self.reader.read(&mut self.buffer[self.end..]) {
+                Ok(0) => self.eof = true,
+                Ok(read) => self.end += read,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => self.error = Some(error.into()),
+            }
+            break;
+        }
+    }
+
+    fn consume(&mut self, len: usize) {
+        self.start += len;
+        self.offset += len as u64;
+    }
+}
+
+impl<R: Read> Iterator for Chars<R> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        while self.error.is_none() {
+            let decoded = match decode(&self.buffer[self.start..self.end]) {
+                Decoded::Incomplete if !self.eof => {
+                    self.refill();
+                    continue;
+                }
+                Decoded::Incomplete if self.start == self.end => return None,
+                Decoded::Incomplete => Decoded::Invalid(self.end - self.start),
+                decoded => decoded,
+            };
+            match decoded {
+                Decoded::Char(char, len) => {
+                    self.consume(len);
+                    return Some(char);
+                }
+                Decoded::Invalid(len) => match self.invalid {
+                    InvalidBytes::Error => self.error = Some(IngestError::InvalidUtf8(self.offset)),
+                    InvalidBytes::Replace => {
+                        self.consume(len);
+                        return Some(char::REPLACEMENT_CHARACTER);
+                    }
+                    InvalidBytes::Skip => self.consume(len),
+                },
+                Decoded::Incomplete => unreachable!(),
+            }
+        }
+        None
+    }
+}
+
+fn decode(bytes: &[u8]) -> Decoded {
+    match bytes.first() {
+        None => Decoded::Incomplete,
+        Some(&byte) if byte.is_ascii() => Decoded::Char(byte as char, 1),
+        Some(_) => {
+            let bytes = &bytes[..bytes.len().min(4)];
+            let error = match std::str::from_utf8(bytes) {
+                Ok(str) => return first_char(str),
+                Err(error) => error,
+            };
+            match (error.valid_up_to(), error.error_len()) {
+                (0, Some(len)) => Decoded::Invalid(len),
+                (0, None) => Decoded::Incomplete,
+                (valid, _) => first_char(std::str::from_utf8(&bytes[..valid]).unwrap()),
+            }
+        }
+    }
+}
+
+fn first_char(str: &str) -> Decoded {
+    let char = str.chars().next().unwrap();
+    Decoded::Char(char, char.len_utf8())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Returns at most one byte per read.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match (self.0.split_first(), buf.first_mut()) {
+                (Some((byte, rest)), Some(dest)) => {
+                    *dest = *byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    pub fn split_code_points() {
+        let text = "Zażółć gęślą jaźń ⇧ 🦀";
+        let read = NgramData::<3>::from_reader(Trickle(text.as_bytes()), InvalidBytes::Error);
+        assert_eq!(read.unwrap(), NgramData::new(text.chars()));
+    }
+
+    #[test]
+    pub fn invalid_bytes() {
+        let bytes = b"a\xc5b\xf0\x9f";
+        let replaced = NgramData::<2>::from_reader(Trickle(bytes), InvalidBytes::Replace);
+        assert_eq!(
+            replaced.unwrap(),
+            NgramData::new("a\u{fffd}b\u{fffd}".chars())
+        );
+        let error = NgramData::<2>::from_reader(Trickle(bytes), InvalidBytes::Error);
+        assert_eq!(error, Err(IngestError::InvalidUtf8(1)));
+    }
+
+    struct Broken;
+
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("disconnected"))
+        }
+    }
+
+    #[test]
+    pub fn read_errors() {
+        let error = NgramData::<2>::from_reader(Broken, InvalidBytes::Error).unwrap_err();
+        assert_eq!(error.clone(), error);
+        let IngestError::Io(io_error) = error else {
+            panic!("expected an io error");
+        };
+        assert_eq!(io_error.kind(), std::io::ErrorKind::Other);
+        assert_eq!(io_error.to_string(), "disconnected");
+    }
+}
@@ -0,0 +1,422 @@
+use super::{IngestError, NgramData};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Languages whose comments and string literals can be recognized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    /// C, C++, C#, Java, JavaScript, TypeScript, Go and alike.
+    C,
+    Rust,
+    Python,
+    /// Shell scripts and alike, with `#` comments.
+    Shell,
+}
+
+struct Syntax {
+    line_comments: &'static [&'static str],
+    block_comments: &'static [(&'static str, &'static str)],
+    // Delimiters of strings with information whether they can span
+    // multiple lines. Longer delimiters come first.
+    strings: &'static [(&'static str, bool)],
+}
+
+impl Language {
+    /// Guesses language from a file extension.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "c" | "h" | "cc" | "cpp" | "hpp" | "cs" | "java" | "js" | "jsx" | "ts" | "tsx"
+            | "go" | "kt" | "swift" => Some(Language::C),
+            "rs" => Some(Language::Rust),
+            "py" => Some(Language::Python),
+            "sh" | "bash" | "zsh" | "rb" | "pl" | "toml" | "yaml" | "yml" => Some(Language::Shell),
+            _ => None,
+        }
+    }
+
+    fn syntax(self) -> Syntax {
+        match self {
+            Language::C => Syntax {
+                line_comments: &["//"],
+                block_comments: &[("/*", "*/")],
+                strings: &[("\"", false), ("'", false), ("`", true)],
+            },
+            // Single quotes also start lifetimes, so only double quoted
+            // strings are recognized.
+            Language::Rust => Syntax {
+                line_comments: &["//"],
+                block_comments: &[("/*", "*/")],
+                strings: &[("\"", true)],
+            },
+            Language::Python => Syntax {
+                line_comments: &["#"],
+                block_comments: &[],
+                strings: &[("\"\"\"", true), ("'''", true), ("\"", false), ("'", false)],
+            },
+            Language::Shell => Syntax {
+                line_comments: &["#"],
+                block_comments: &[],
+                strings: &[("\"", true), ("'", true)],
+            },
+        }
+    }
+}
+
+/// Preprocessing of source code, which makes counted ngrams closer to the
+/// ones typed in an editor.
+#[derive(Clone, Debug, Default)]
+pub struct CodeOptions {
+    /// Remove whitespace at the start of lines, which is usually inserted
+    /// by the editor.
+    pub strip_indentation: bool,
+    /// Pairs of opening and closing characters inserted together by the
+    /// editor, e.g. brackets. Closing characters matching an earlier
+    /// opening one are removed.
+    pub auto_pairs: Vec<(char, char)>,
+    /// Remove comments, including their delimiters.
+    pub skip_comments: bool,
+    /// Remove contents of string literals, keeping their delimiters.
+    pub skip_strings: bool,
+    /// Files and directories in searched directories matching any of
+    /// these globs are skipped.
+    /// Globs without `/` are matched against names, others against
+    /// paths relative to the searched directory. `*` matches any part of
+    /// a name, `**` any part of a path and `?` a single character.
+    pub exclude: Vec<String>,
+    /// Files larger than this many bytes are skipped.
+    pub max_size: Option<u64>,
+}
+
+/// Number of files counted and skipped by
+/// [`NgramData::add_source_files`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SourceFiles {
+    pub counted: usize,
+    /// Number of skipped files, excluded directories and symbolic links
+    /// found in directories. Files in excluded directories aren't
+    /// counted separately.
+    pub skipped: usize,
+}
+
+impl CodeOptions {
+    /// Preprocesses source code in `language`. Without a language
+    /// comments and strings are kept.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{CodeOptions, Language};
+    /// let options = CodeOptions {
+    ///     strip_indentation: true,
+    ///     auto_pairs: vec![('(', ')'), ('"', '"')],
+    ///     skip_comments: true,
+    ///     ..CodeOptions::default()
+    /// };
+    /// let code = "if x {\n    print(\"hi\"); // greet\n}";
+    /// let code = options.preprocess(code, Some(Language::C));
+    /// assert_eq!(code, "if x {\nprint(\"hi; \n}");
+    /// ```
+    pub fn preprocess(&self, code: &str, language: Option<Language>) -> String {
+        let mut code = match language {
+            Some(language) => self.skip_syntax(code, language.syntax()),
+            None => code.to_string(),
+        };
+        if self.strip_indentation {
+            code = code
+                .split('\n')
+                .map(|line| line.trim_start_matches([' ', '\t']))
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+        if !self.auto_pairs.is_empty() {
+            code = self.drop_auto_closed(&code);
+        }
+        code
+    }
+
+    fn skip_syntax(&self, code: &str, syntax: Syntax) -> String {
+        let mut res = String::with_capacity(code.len());
+        let mut i = 0;
+        while let Some(char) = code[i..].chars().next() {
+            let rest = &code[i..];
+            let comment = syntax
+                .line_comments
+                .iter()
+                .find(|start| rest.starts_with(**start))
+                .map(|_| rest.find('\n').unwrap_or(rest.len()))
+                .or_else(|| {
+                    let (start, end) = syntax
+                        .block_comments
+                        .iter()
+                        .find(|(start, _)| rest.starts_with(start))?;
+                    let len = rest[start.len()..]
+                        .find(end)
+                        .map_or(rest.len(), |i| start.len() + i + end.len());
+                    Some(len)
+                });
+            if let Some(len) = comment {
+                if !self.skip_comments {
+                    res.push_str(&rest[..len]);
+                }
+                i += len;
+                continue;
+            }
+            let string = syntax.strings.iter().find_map(|&(delimiter, multiline)| {
+                let len = string_len(rest.strip_prefix(delimiter)?, delimiter, multiline)?;
+                Some((delimiter, len))
+            });
+            match string {
+                Some((delimiter, len)) if self.skip_strings => {
+                    res.push_str(delimiter);
+                    res.push_str(delimiter);
+                    i += len + 2 * delimiter.len();
+                }
+                Some((delimiter, len)) => {
+                    let len = len + 2 * delimiter.len();
+                    res.push_str(&rest[..len]);
+                    i += len;
+                }
+                None => {
+                    res.push(char);
+                    i += char.len_utf8();
+                }
+            }
+        }
+        res
+    }
+
+    fn drop_auto_closed(&self, code: &str) -> String {
+        // Closing characters expected after opening ones typed so far.
+        let mut expected = Vec::new();
+        code.chars()
+            .filter(|&char| {
+                if expected.last() == Some(&char) {
+                    expected.pop();
+                    return false;
+                }
+                if let Some(&(_, close)) = self.auto_pairs.iter().find(|(open, _)| *open == char) {
+                    expected.push(close);
+                }
+                true
+            })
+            .collect()
+    }
+
+    /// Checks if file at `path` of `size` bytes should be counted.
+    /// `path` is relative to the searched directory.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::CodeOptions;
+    /// use std::path::Path;
+    /// let options = CodeOptions {
+    ///     exclude: vec!["*.min.js".into(), "target/**".into()],
+    ///     max_size: Some(1000),
+    ///     ..CodeOptions::default()
+    /// };
+    /// assert!(options.includes(Path::new("src/main.js"), 100));
+    /// assert!(!options.includes(Path::new("src/main.js"), 2000));
+    /// assert!(!options.includes(Path::new("dist/app.min.js"), 100));
+    /// assert!(!options.includes(Path::new("target/debug/build.rs"), 100));
+    /// ```
+    pub fn includes(&self, path: &Path, size: u64) -> bool {
+        self.max_size.is_none_or(|max| size <= max) && !self.is_excluded(path)
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        let path = path.to_string_lossy().replace('\\', "/");
+        let path = path.trim_start_matches("./");
+        let name = path.rsplit('/').next().unwrap_or(path);
+        self.exclude.iter().any(|glob| match glob.contains('/') {
+            true => matches(glob.as_bytes(), path.as_bytes()),
+            false => matches(glob.as_bytes(), name.as_bytes()),
+        })
+    }
+}
+
+// Length of a string after its opening delimiter, up to its closing one.
+// Strings which can't span multiple lines have to end on the same line.
+fn string_len(rest: &str, delimiter: &str, multiline: bool) -> Option<usize> {
+    let mut chars = rest.char_indices();
+    while let Some((i, char)) = chars.next() {
+        match char {
+            '\\' => {
+                chars.next();
+            }
+            '\n' if !multiline => return None,
+            _ if rest[i..].starts_with(delimiter) => return Some(i),
+            _ => (),
+        }
+    }
+    None
+}
+
+// Matches path against a glob.
+fn matches(glob: &[u8], path: &[u8]) -> bool {
+    match (glob, path) {
+        ([], path) => path.is_empty(),
+        ([b'*', b'*', glob @ ..], path) => {
+            let glob = glob.strip_prefix(b"/").unwrap_or(glob);
+            (0..=path.len()).any(|i| matches(glob, &path[i..]))
+        }
+        ([b'*', glob @ ..], path) => (0..=path.len())
+            .take_while(|&i| i == 0 || path[i - 1] != b'/')
+            .any(|i| matches(glob, &path[i..])),
+        ([b'?', glob @ ..], [char, path @ ..]) => *char != b'/' && matches(glob, path),
+        ([expected, glob @ ..], [char, path @ ..]) => expected == char && matches(glob, path),
+        _ => false,
+    }
+}
+
+impl<const N: usize> NgramData<N> {
+    /// Counts ngrams in source files preprocessed with `options`.
+    /// Directories are searched recursively, without following symbolic
+    /// links inside them. Paths given directly aren't matched against
+    /// [`CodeOptions::exclude`]. Every file is a separate document and its
+    /// language is guessed from its extension. Files which aren't valid
+    /// UTF-8 are skipped, as they are usually binary.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails. Files preceding it stay
+    /// counted.
+    pub fn add_source_files<P>(
+        &mut self,
+        paths: impl IntoIterator<Item = P>,
+        options: &CodeOptions,
+    ) -> Result<SourceFiles, IngestError>
+    where
+        P: AsRef<Path>,
+    {
+        let mut files = SourceFiles::default();
+        for path in paths {
+            let path = path.as_ref();
+            let metadata = fs::metadata(path).map_err(IngestError::from)?;
+            if metadata.is_dir() {
+                self.add_source_dir(path, Path::new(""), options, &mut files)?;
+            } else if options.max_size.is_some_and(|max| metadata.len() > max) {
+                files.skipped += 1;
+            } else {
+                self.add_source_file(path, options, &mut files)?;
+            }
+        }
+        Ok(files)
+    }
+
+    // Counts files in directory at `path`, whose path relative to the
+    // searched directory is `relative`.
+    fn add_source_dir(
+        &mut self,
+        path: &Path,
+        relative: &Path,
+        options: &CodeOptions,
+        files: &mut SourceFiles,
+    ) -> Result<(), IngestError> {
+        let mut names: Vec<_> = fs::read_dir(path)
+            .and_then(|entries| {
+                entries
+                    .map(|entry| Ok(entry?.file_name()))
+                    .collect::<io::Result<_>>()
+            })
+            .map_err(IngestError::from)?;
+        names.sort_unstable();
+        for name in names {
+            let (path, relative) = (path.join(&name), relative.join(&name));
+            let metadata = fs::symlink_metadata(&path).map_err(IngestError::from)?;
+            if metadata.is_symlink() {
+                files.skipped += 1;
+            } else if metadata.is_dir() {
+                match options.is_excluded(&relative) {
+                    true => files.skipped += 1,
+                    false => self.add_source_dir(&path, &relative, options, files)?,
+                }
+            } else if !options.includes(&relative, metadata.len()) {
+                files.skipped += 1;
+            } else {
+                self.add_source_file(&path, options, files)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_source_file(
+        &mut self,
+        path: &Path,
+        options: &CodeOptions,
+        files: &mut SourceFiles,
+    ) -> Result<(), IngestError> {
+        let bytes = fs::read(path).map_err(IngestError::from)?;
+        let Ok(code) = String::from_utf8(bytes) else {
+            files.skipped += 1;
+            return Ok(());
+        };
+        let language = path
+            .extension()
+            .and_then(|extension| Language::from_extension(&extension.to_string_lossy()));
+        self.add(options.preprocess(&code, language).chars());
+        files.counted += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn syntax() {
+        let options = CodeOptions {
+            skip_comments: true,
+            skip_strings: true,
+            ..CodeOptions::default()
+        };
+        let code = "x = 'a # b' # c\ns = \"\"\"d\ne\"\"\"\n";
+        let code = options.preprocess(code, Some(Language::Python));
+        assert_eq!(code, "x = '' \ns = \"\"\"\"\"\"\n");
+        let code = "f<'a>(\"x // y\\\"\") /* z\n */;";
+        let code = options.preprocess(code, Some(Language::Rust));
+        assert_eq!(code, "f<'a>(\"\") ;");
+        let options = CodeOptions {
+            skip_strings: true,
+            ..CodeOptions::default()
+        };
+        let code = options.preprocess("a = \"b\n", Some(Language::C));
+        assert_eq!(code, "a = \"b\n");
+    }
+
+    #[test]
+    pub fn globs() {
+        assert!(matches(b"*.rs", b"main.rs"));
+        assert!(!matches(b"*.rs", b"src/main.rs"));
+        assert!(matches(b"src/**/*.rs", b"src/main.rs"));
+        assert!(matches(b"src/**/*.rs", b"src/a/b/main.rs"));
+        assert!(matches(b"**/gen/*", b"a/gen/b"));
+        assert!(matches(b"?.c", b"a.c"));
+        assert!(!matches(b"?.c", b"ab.c"));
+    }
+
+    #[test]
+    pub fn walk() {
+        let root = std::env::temp_dir().join(format!("algae-walk-{}", std::process::id()));
+        fs::create_dir_all(root.join("src/gen")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(root.join("src/gen/table.rs"), "const T: u8 = 0;").unwrap();
+        fs::write(root.join("target/out.rs"), "x").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&root, root.join("src/loop")).unwrap();
+        let options = CodeOptions {
+            exclude: vec!["src/gen/**".into(), "target".into()],
+            ..CodeOptions::default()
+        };
+        let mut corpus = NgramData::<2>::empty();
+        let files = corpus.add_source_files([&root], &options).unwrap();
+        assert_eq!(files.counted, 1);
+        // Excluded file, excluded directory and the link.
+        assert_eq!(files.skipped, if cfg!(unix) { 3 } else { 2 });
+        assert_eq!(corpus, NgramData::new("fn main() {}".chars()));
+        fs::remove_dir_all(root).unwrap();
+    }
+}
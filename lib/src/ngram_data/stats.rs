@@ -0,0 +1,133 @@
+use super::NgramData;
+use std::collections::HashSet;
+
+/// Share of characters of a corpus covered by a charset.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Coverage {
+    /// Number of characters in the charset.
+    pub covered: u64,
+    /// Number of all characters.
+    pub total: u64,
+    /// Characters missing from the charset with their counts, from the
+    /// most frequent.
+    pub missing: Vec<(char, u64)>,
+}
+
+impl Coverage {
+    /// Fraction of characters in the charset.
+    pub fn fraction(&self) -> f64 {
+        match self.total {
+            0 => 0.0,
+            total => self.covered as f64 / total as f64,
+        }
+    }
+}
+
+impl<const N: usize> NgramData<N> {
+    /// Total number of ngrams of length `len`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if N < len.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let corpus = NgramData::<3>::new("abcd".chars());
+    /// assert_eq!(corpus.total(1), 4);
+    /// assert_eq!(corpus.total(3), 2);
+    /// ```
+    pub fn total(&self, len: usize) -> u64 {
+        self.contract(len).values().sum()
+    }
+
+    /// Number of distinct ngrams of length `len`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if N < len.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let corpus = NgramData::<2>::new("abab".chars());
+    /// assert_eq!(corpus.distinct(2), 2);
+    /// ```
+    pub fn distinct(&self, len: usize) -> usize {
+        self.contract(len).len()
+    }
+
+    /// Characters with their counts, from the most frequent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let corpus = NgramData::<2>::new("abcb".chars());
+    /// assert_eq!(corpus.char_frequencies()[0], ('b', 2));
+    /// ```
+    pub fn char_frequencies(&self) -> Vec<(char, u64)> {
+        let mut chars: Vec<_> = self
+            .contract(1)
+            .into_iter()
+            .map(|(char, count)| (char[0], count))
+            .collect();
+        chars.sort_unstable_by(|(c1, n1), (c2, n2)| n2.cmp(n1).then(c1.cmp(c2)));
+        chars
+    }
+
+    /// Shannon entropy of ngrams of length `len` in bits.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if N < len.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let corpus = NgramData::<2>::new("abcd".chars());
+    /// assert_eq!(corpus.entropy(1), 2.0);
+    /// ```
+    pub fn entropy(&self, len: usize) -> f64 {
+        let counts = self.contract(len);
+        let total: u64 = counts.values().sum();
+        counts
+            .values()
+            .map(|&count| {
+                let p = count as f64 / total as f64;
+                -p * p.log2()
+            })
+            .sum()
+    }
+
+    /// Calculates how many characters of the corpus are in `charset`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::NgramData;
+    /// let corpus = NgramData::<2>::new("Hello, world".chars());
+    /// let coverage = corpus.coverage('a'..='z');
+    /// assert_eq!((coverage.covered, coverage.total), (9, 12));
+    /// assert_eq!(coverage.missing[0].0, ' ');
+    /// ```
+    pub fn coverage(&self, charset: impl IntoIterator<Item = char>) -> Coverage {
+        let charset: HashSet<_> = charset.into_iter().collect();
+        let mut coverage = Coverage {
+            covered: 0,
+            total: 0,
+            missing: Vec::new(),
+        };
+        for (char, count) in self.char_frequencies() {
+            coverage.total += count;
+            match charset.contains(&char) {
+                true => coverage.covered += count,
+                false => coverage.missing.push((char, count)),
+            }
+        }
+        coverage
+    }
+}
@@ -0,0 +1,449 @@
+use super::NgramData;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Read, Write};
+
+/// Format of plain tables of ngram counts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableFormat {
+    /// Rows of `ngram<TAB>count`. Tabs, line breaks and backslashes in
+    /// ngrams are escaped with a backslash. Backslashes which don't start
+    /// one of these escapes are read as they are, as tables written by
+    /// other tools often don't escape them.
+    Tsv,
+    /// Rows of `ngram,count`. Ngrams are quoted when needed.
+    Csv,
+    /// Object mapping ngrams to their counts.
+    Json,
+}
+
+impl<const N: usize> NgramData<N> {
+    /// Writes counts of ngrams of length `N` and `N - 1` to `writer`.
+    /// Shorter ngrams let [`NgramData::read_table`] find where documents
+    /// end. Rows are sorted from the most frequent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{NgramData, TableFormat};
+    /// let corpus = NgramData::<2>::new("aab".chars());
+    /// let mut table = Vec::new();
+    /// corpus.write_table(&mut table, TableFormat::Tsv).unwrap();
+    /// let table = String::from_utf8(table).unwrap();
+    /// assert_eq!(table, "aa\t1\nab\t1\na\t2\nb\t1\n");
+    /// ```
+    pub fn write_table(&self, mut writer: impl Write, format: TableFormat) -> io::Result<()> {
+        let mut rows = Vec::new();
+        for len in (N.saturating_sub(1).max(1)..=N).rev() {
+            let mut counts: Vec<_> = self.contract(len).into_iter().collect();
+            counts.sort_unstable_by(|(n1, c1), (n2, c2)| c2.cmp(c1).then(n1.cmp(n2)));
+            rows.extend(counts);
+        }
+        match format {
+            TableFormat::Tsv => {
+                for (ngram, count) in rows {
+                    let ngram: String = ngram.into_iter().map(escape).collect();
+                    writeln!(writer, "{ngram}\t{count}")?;
+                }
+            }
+            TableFormat::Csv => {
+                for (ngram, count) in rows {
+                    let ngram: String = ngram.into_iter().collect();
+                    match ngram.contains([',', '"', '\n', '\r']) {
+                        true => writeln!(writer, "\"{}\",{count}", ngram.replace('"', "\"\""))?,
+                        false => writeln!(writer, "{ngram},{count}")?,
+                    }
+                }
+            }
+            TableFormat::Json => {
+                let rows: Vec<_> = rows
+                    .into_iter()
+                    .map(|(ngram, count)| (ngram.into_iter().collect::<String>(), count))
+                    .collect();
+                serde_json::to_writer(&mut writer, &JsonRows(&rows))?;
+                writeln!(writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads `NgramData` from a table of ngram counts. Rows with ngrams
+    /// of length other than `N` and `N - 1` are ignored. A first row whose
+    /// count isn't a number is treated as a header.
+    ///
+    /// Ends of documents can be found only when the table contains
+    /// consistent counts of ngrams of length `N - 1`, like tables
+    /// written with [`NgramData::write_table`]. Otherwise the result
+    /// [is contraction only](NgramData::is_contraction_only). Documents
+    /// shorter than `N - 1` are lost either way and starts of documents
+    /// are only approximated for
+    /// [`NgramData::expand_with_context`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails, the table is malformed or an
+    /// ngram occurs in it twice.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use algae_lib::{NgramData, TableFormat};
+    /// let table = r#"{"ab": 2, "bc": 1, "a": 2, "b": 2, "c": 1}"#;
+    /// let corpus = NgramData::<2>::read_table(table.as_bytes(), TableFormat::Json).unwrap();
+    /// assert!(!corpus.is_contraction_only());
+    /// assert_eq!(corpus.ngrams::<1>().get(&['b']), Some(&2));
+    ///
+    /// let table = "ab\t2\nbc\t1\n";
+    /// let corpus = NgramData::<2>::read_table(table.as_bytes(), TableFormat::Tsv).unwrap();
+    /// assert!(corpus.is_contraction_only());
+    /// assert_eq!(corpus.ngrams::<1>().get(&['c']), None);
+    /// ```
+    pub fn read_table(mut reader: impl Read, format: TableFormat) -> Result<Self, TableError> {
+        let rows = match format {
+            TableFormat::Json => serde_json::from_reader::<_, JsonTable>(reader)
+                .map_err(TableError::Json)?
+                .0
+                .into_iter()
+                .map(|(ngram, count)| (ngram.chars().collect(), count))
+                .collect(),
+            _ => {
+                let mut text = String::new();
+                reader.read_to_string(&mut text).map_err(TableError::Io)?;
+                let rows = match format {
+                    TableFormat::Tsv => tsv_rows(&text),
+                    _ => csv_rows(&text)?,
+                };
+                parse_counts(rows)?
+            }
+        };
+        let mut ngrams = HashMap::new();
+        let mut shorter = HashMap::new();
+        for (ngram, count) in rows {
+            match <[char; N]>::try_from(&ngram[..]) {
+                Ok(ngram) => drop(ngrams.insert(ngram, count)),
+                Err(_) if ngram.len() + 1 == N => drop(shorter.insert(ngram, count)),
+                Err(_) => (),
+            }
+        }
+        Ok(Self::from_counts(ngrams, &shorter))
+    }
+
+    // Splits counts of ngrams into ones followed by more text and tails,
+    // based on counts of ngrams shorter by one. Choice of tails and heads
+    // among ngrams sharing the same ends doesn't change contractions.
+    fn from_counts(ngrams: HashMap<[char; N], u64>, shorter: &HashMap<Vec<char>, u64>) -> Self {
+        let mut res = Self {
+            ngrams,
+            ..Self::default()
+        };
+        if N == 1 {
+            return res;
+        }
+        let mut sorted: Vec<_> = res.ngrams.keys().copied().collect();
+        sorted.sort_unstable();
+        let mut prefixes = HashMap::<_, u64>::new();
+        let mut suffixes = HashMap::<_, u64>::new();
+        for (ngram, &count) in &res.ngrams {
+            *prefixes.entry(&ngram[..N - 1]).or_default() += count;
+            *suffixes.entry(&ngram[1..]).or_default() += count;
+        }
+        // Ngrams of length `N - 1` which aren't followed by or don't
+        // follow any counted ngram are the ends or starts of documents.
+        // Those which are both are counted as short documents.
+        let mut ends = HashMap::new();
+        let mut starts = HashMap::new();
+        for (shorter, &count) in shorter {
+            let prefix = prefixes.get(&shorter[..]).copied().unwrap_or(0);
+            let suffix = suffixes.get(&shorter[..]).copied().unwrap_or(0);
+            let (Some(end), Some(start)) = (count.checked_sub(prefix), count.checked_sub(suffix))
+            else {
+                res.contraction_only = true;
+                continue;
+            };
+            let short = end.saturating_sub(suffix).max(start.saturating_sub(prefix));
+            if short > end.min(start) {
+                res.contraction_only = true;
+                continue;
+            }
+            if short > 0 {
+                res.short.insert(shorter.clone(), short);
+            }
+            ends.insert(&shorter[..], end - short);
+            starts.insert(&shorter[..], start - short);
+        }
+        let known = |ngram: &&[char]| shorter.contains_key(*ngram);
+        res.contraction_only |= !prefixes.keys().chain(suffixes.keys()).all(known);
+        if res.contraction_only {
+            res.short.clear();
+            return res;
+        }
+        let mut tails = HashMap::new();
+        let mut heads = HashMap::new();
+        for ngram in sorted {
+            let count = res.ngrams[&ngram];
+            let end = ends.get_mut(&ngram[1..]).unwrap();
+            let tail = count.min(*end);
+            *end -= tail;
+            if tail > 0 {
+                tails.insert(ngram, tail);
+            }
+            let start = starts.get_mut(&ngram[..N - 1]).unwrap();
+            let head = count.min(*start);
+            *start -= head;
+            if head > 0 {
+                heads.insert(ngram, head);
+            }
+        }
+        for (tail, count) in &tails {
+            let ngram = res.ngrams.get_mut(tail).unwrap();
+            *ngram -= count;
+            if *ngram == 0 {
+                res.ngrams.remove(tail);
+            }
+        }
+        res.tails = tails;
+        res.heads = heads;
+        res
+    }
+}
+
+// Serializes rows as a JSON object, keeping their order.
+struct JsonRows<'a>(&'a [(String, u64)]);
+
+impl serde::Serialize for JsonRows<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(ngram, count)| (ngram, count)))
+    }
+}
+
+// Deserializes a JSON object, failing on duplicate keys which would
+// silently overwrite each other in a map.
+struct JsonTable(Vec<(String, u64)>);
+
+impl<'de> serde::Deserialize<'de> for JsonTable {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = JsonTable;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                write!(f, "an object mapping ngrams to counts")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<JsonTable, A::Error> {
+                let mut rows = Vec::new();
+                let mut seen = HashSet::new();
+                while let Some((ngram, count)) = map.next_entry::<String, u64>()? {
+                    if !seen.insert(ngram.clone()) {
+                        let error = format!("duplicate ngram {ngram:?}");
+                        return Err(serde::de::Error::custom(error));
+                    }
+                    rows.push((ngram, count));
+                }
+                Ok(JsonTable(rows))
+            }
+        }
+
+        deserializer.deserialize_map(Visitor)
+    }
+}
+
+fn escape(char: char) -> String {
+    match char {
+        '\\' => "\\\\".to_string(),
+        '\t' => "\\t".to_string(),
+        '\n' => "\\n".to_string(),
+        '\r' => "\\r".to_string(),
+        char => char.to_string(),
+    }
+}
+
+// Splits TSV into rows of fields together with their line numbers.
+fn tsv_rows(text: &str) -> Vec<(usize, Vec<String>)> {
+    let mut rows = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let mut fields = vec![String::new()];
+        let mut chars = line.chars();
+        while let Some(char) = chars.next() {
+            let field = fields.last_mut().unwrap();
+            match char {
+                '\t' => fields.push(String::new()),
+                '\\' => {
+                    let escaped = match chars.clone().next() {
+                        Some('\\') => Some('\\'),
+                        Some('t') => Some('\t'),
+                        Some('n') => Some('\n'),
+                        Some('r') => Some('\r'),
+                        _ => None,
+                    };
+                    match escaped {
+                        Some(escaped) => {
+                            chars.next();
+                            field.push(escaped);
+                        }
+                        // Unknown escapes are literal backslashes.
+                        None => field.push('\\'),
+                    }
+                }
+                char => field.push(char),
+            }
+        }
+        rows.push((i + 1, fields));
+    }
+    rows
+}
+
+// Splits CSV into rows of fields together with the line numbers they
+// start at. Quoted fields can contain line breaks.
+fn csv_rows(text: &str) -> Result<Vec<(usize, Vec<String>)>, TableError> {
+    let mut rows = Vec::new();
+    let mut line = 1;
+    let mut chars = text.chars().peekable();
+    while chars.peek().is_some() {
+        let start = line;
+        let mut fields = vec![String::new()];
+        let mut quoted = false;
+        loop {
+            let field = fields.last_mut().unwrap();
+            match (chars.next(), quoted) {
+                (None, true) => return Err(TableError::InvalidRow(start)),
+                (None, false) => break,
+                (Some('"'), true) if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                (Some('"'), true) => quoted = false,
+                (Some('"'), false) if field.is_empty() => quoted = true,
+                (Some('\n'), false) => {
+                    line += 1;
+                    break;
+                }
+                (Some('\r'), false) if chars.peek() == Some(&'\n') => (),
+                (Some(','), false) => fields.push(String::new()),
+                (Some(char), _) => {
+                    if char == '\n' {
+                        line += 1;
+                    }
+                    field.push(char);
+                }
+            }
+        }
+        if fields != [""] {
+            rows.push((start, fields));
+        }
+    }
+    Ok(rows)
+}
+
+fn parse_counts(rows: Vec<(usize, Vec<String>)>) -> Result<Vec<(Vec<char>, u64)>, TableError> {
+    let mut res = Vec::with_capacity(rows.len());
+    let mut seen = HashMap::new();
+    for (i, (line, fields)) in rows.into_iter().enumerate() {
+        let [ngram, count] =
+            <[String; 2]>::try_from(fields).map_err(|_| TableError::InvalidRow(line))?;
+        let count = match count.trim().parse() {
+            Ok(count) => count,
+            Err(_) if i == 0 => continue,
+            Err(_) => return Err(TableError::InvalidCount(line)),
+        };
+        if seen.insert(ngram.clone(), line).is_some() {
+            return Err(TableError::DuplicateNgram(line));
+        }
+        res.push((ngram.chars().collect(), count));
+    }
+    Ok(res)
+}
+
+#[derive(Debug)]
+pub enum TableError {
+    Io(io::Error),
+    /// Row starting at given line doesn't consist of an ngram and a count.
+    InvalidRow(usize),
+    /// Count at given line isn't a non-negative integer.
+    InvalidCount(usize),
+    /// Ngram at given line already occurred in the table.
+    DuplicateNgram(usize),
+    Json(serde_json::Error),
+}
+
+impl Display for TableError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use TableError::*;
+        match self {
+            Io(error) => write!(f, "failed to read table: {error}"),
+            InvalidRow(line) => write!(f, "invalid row at line {line}"),
+            InvalidCount(line) => write!(f, "invalid count at line {line}"),
+            DuplicateNgram(line) => write!(f, "duplicate ngram at line {line}"),
+            Json(error) => write!(f, "invalid JSON table: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for TableError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TableError::Io(error) => Some(error),
+            TableError::Json(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn round_trip() {
+        let documents = ["quick, \"brown\"\tfox", "a\nb", "jumps", "ok"];
+        let corpus = NgramData::<3>::from_documents(documents.map(str::chars));
+        for format in [TableFormat::Tsv, TableFormat::Csv, TableFormat::Json] {
+            let mut table = Vec::new();
+            corpus.write_table(&mut table, format).unwrap();
+            let read = NgramData::<3>::read_table(&table[..], format).unwrap();
+            assert!(!read.is_contraction_only());
+            assert_eq!(read.ngrams::<3>(), corpus.ngrams::<3>());
+            assert_eq!(read.ngrams::<2>(), corpus.ngrams::<2>());
+        }
+    }
+
+    #[test]
+    pub fn errors() {
+        let read = |table: &str| NgramData::<2>::read_table(table.as_bytes(), TableFormat::Csv);
+        assert!(read("ngram,count\nab,1\n").is_ok());
+        assert!(matches!(read("ab,1\ncd\n"), Err(TableError::InvalidRow(2))));
+        assert!(matches!(
+            read("ab,1\ncd,x\n"),
+            Err(TableError::InvalidCount(2))
+        ));
+        assert!(matches!(
+            read("\"a\nb\",1\nab,1\nab,2"),
+            Err(TableError::DuplicateNgram(4))
+        ));
+        let json = |table: &str| NgramData::<2>::read_table(table.as_bytes(), TableFormat::Json);
+        assert!(json(r#"{"ab": 1, "ba": 1}"#).is_ok());
+        assert!(matches!(
+            json(r#"{"ab": 1, "ab": 2}"#),
+            Err(TableError::Json(_))
+        ));
+    }
+
+    #[test]
+    pub fn tsv_backslashes() {
+        let table = "a\\\t1\n\\x\t1\n\\\\d\t2\nb\\\t3\n";
+        let corpus = NgramData::<2>::read_table(table.as_bytes(), TableFormat::Tsv).unwrap();
+        let bigrams = corpus.ngrams::<2>();
+        // Only the backslash escaped with another one is unescaped.
+        assert_eq!(bigrams.get(&['\\', 'x']), Some(&1));
+        assert_eq!(bigrams.get(&['\\', 'd']), Some(&2));
+        assert_eq!(bigrams.get(&['a', '\\']), Some(&1));
+        assert_eq!(bigrams.get(&['b', '\\']), Some(&3));
+    }
+}